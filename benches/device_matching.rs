@@ -0,0 +1,83 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use laptop_selector::{get_best_match, Cpu, DeviceIndex, DeviceKind};
+
+/// Catalog of size similar to cpubenchmark.net list
+fn catalog() -> Vec<Cpu> {
+    let mut names = vec![String::from("Unknown cpu")];
+    for generation in 2..=14 {
+        for tier in [3, 5, 7, 9] {
+            for model in (100..=990).step_by(30) {
+                for suffix in ["", "H", "U", "HX", "P"] {
+                    names.push(format!("Intel Core i{tier}-{generation}{model:03}{suffix}"));
+                }
+            }
+        }
+    }
+    for series in [3, 5, 7, 9] {
+        for model in (1200..=8990).step_by(70) {
+            for suffix in ["U", "H", "HS", "X"] {
+                names.push(format!("AMD Ryzen {series} {model}{suffix}"));
+            }
+        }
+    }
+    for model in 1000..1400 {
+        names.push(format!("Intel Celeron N{model}"));
+    }
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| Cpu {
+            id: id as i64,
+            name,
+            url: String::new(),
+            score: 0,
+            score_updated_at: None,
+            single_thread_score: None,
+            tdp: None,
+            cores: None,
+            threads: None,
+        })
+        .collect()
+}
+
+/// Recognized by rules and falling back to fuzzy matching compositions
+const COMPOSITIONS: &[&str] = &[
+    "Екран 15.6\" IPS (1920x1080) Full HD 144 Гц / Intel Core i5-12450H (2.0 - 4.4 ГГц) / RAM 16 ГБ / SSD 512 ГБ / nVidia GeForce RTX 3050, 4 ГБ / без ОС / 2.2 кг",
+    "Екран 15.6\" IPS (1920x1080) Full HD / AMD Ryzen 5 7520U (2.8 - 4.3 ГГц) / RAM 8 ГБ / SSD 512 ГБ / AMD Radeon 610M / без ОС / 1.62 кг",
+    "Екран 14\" IPS (1920x1200) WUXGA / Intel Core i7-1355U (1.7 - 5.0 ГГц) / RAM 16 ГБ / SSD 1 ТБ / Intel Iris Xe Graphics / Windows 11 Home / 1.4 кг",
+    "Екран 15.6\" TN+film (1366x768) HD / Intel Celeron N4500 (1.1 - 2.8 ГГц) / RAM 4 ГБ / eMMC 128 ГБ / Intel UHD Graphics / без ОС / 1.7 кг",
+    "Екран 11.6\" IPS (1920x1080) / Intel Celeron N1100 / RAM 4 ГБ / eMMC 64 ГБ / Intel UHD Graphics 600 / Windows 11 / 1.1 кг",
+    "Екран 16\" IPS (2560x1600) WQXGA 165 Гц / AMD Ryzen 7 7840HS (3.8 - 5.1 ГГц) / RAM 32 ГБ / SSD 1 ТБ / nVidia GeForce RTX 4070, 8 ГБ / без ОС / 2.3 кг",
+];
+
+pub fn matching_benchmark(c: &mut Criterion) {
+    let devices = catalog();
+    let index = DeviceIndex::new(devices.clone());
+    for composition in COMPOSITIONS {
+        assert_eq!(
+            get_best_match(composition, DeviceKind::Cpu, &devices),
+            index.best_match(composition, DeviceKind::Cpu),
+            "{composition}"
+        );
+    }
+
+    c.bench_function("match cpus by walking all devices", |b| {
+        b.iter(|| {
+            COMPOSITIONS
+                .iter()
+                .map(|composition| get_best_match(composition, DeviceKind::Cpu, &devices))
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("match cpus with device index", |b| {
+        b.iter(|| {
+            COMPOSITIONS
+                .iter()
+                .map(|composition| index.best_match(composition, DeviceKind::Cpu))
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+criterion_group!(benches, matching_benchmark);
+criterion_main!(benches);
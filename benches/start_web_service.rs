@@ -1,10 +1,11 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use laptop_selector::prepare_laptop_requests_router;
+use laptop_selector::{prepare_laptop_requests_router, DatabaseSettings};
 
 pub fn initialization_benchmark(c: &mut Criterion) {
+    let settings = DatabaseSettings::default();
     c.bench_function("generate routes and data", |b| {
         b.to_async(tokio::runtime::Runtime::new().unwrap())
-            .iter(|| prepare_laptop_requests_router())
+            .iter(|| prepare_laptop_requests_router(&settings))
     });
 }
 
@@ -0,0 +1,9 @@
+-- No foreign key to laptop: scrapper replaces laptop rows, which would cascade into history
+CREATE TABLE IF NOT EXISTS laptop_price_history (
+    id INTEGER PRIMARY KEY NOT NULL,
+    laptop_id INTEGER NOT NULL,
+    price INTEGER NOT NULL,
+    observed_at VARCHAR(19) NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX IF NOT EXISTS laptop_price_history_laptop_id
+    ON laptop_price_history(laptop_id, observed_at);
@@ -0,0 +1 @@
+ALTER TABLE laptop ADD COLUMN shop VARCHAR(63) NOT NULL DEFAULT 'rozetka';
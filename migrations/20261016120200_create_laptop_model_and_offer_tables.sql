@@ -0,0 +1,43 @@
+-- Same laptop can be sold by several shops, so laptop is split into model and its offers
+CREATE TABLE IF NOT EXISTS laptop_model (
+    id INTEGER PRIMARY KEY NOT NULL,
+    model_key VARCHAR(255) NOT NULL UNIQUE,
+    image VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    composition VARCHAR(255),
+    cpu_id INTEGER NOT NULL,
+    gpu_id INTEGER NOT NULL,
+    CONSTRAINT fk_cpu
+        FOREIGN KEY(cpu_id)
+        REFERENCES cpu(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_gpu
+        FOREIGN KEY(gpu_id)
+        REFERENCES gpu(id)
+        ON DELETE CASCADE
+);
+CREATE TABLE IF NOT EXISTS offer (
+    id INTEGER PRIMARY KEY NOT NULL,
+    model_id INTEGER NOT NULL,
+    shop VARCHAR(63) NOT NULL,
+    product_id INTEGER NOT NULL,
+    url VARCHAR(255) NOT NULL,
+    price INTEGER NOT NULL,
+    availability VARCHAR(63) NOT NULL DEFAULT 'available',
+    UNIQUE(shop, product_id),
+    CONSTRAINT fk_model
+        FOREIGN KEY(model_id)
+        REFERENCES laptop_model(id)
+        ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS offer_model_id ON offer(model_id, price);
+-- Model key of existing laptops is unknown, scrapper moves their offers to real models
+-- and removes models left without offers. Missing composition is NULL instead of empty string
+INSERT INTO laptop_model(id, model_key, image, description, composition, cpu_id, gpu_id)
+    SELECT id, shop || ':' || id, image, description, NULLIF(composition, ''), cpu_id, gpu_id
+    FROM laptop;
+-- Offers keep ids of laptops, so recorded price history stays attached to them
+INSERT INTO offer(id, model_id, shop, product_id, url, price)
+    SELECT id, id, shop, id, url, price FROM laptop;
+DROP TABLE laptop;
+ALTER TABLE laptop_price_history RENAME COLUMN laptop_id TO offer_id;
@@ -0,0 +1,5 @@
+-- Column added by ALTER can't default to CURRENT_TIMESTAMP, scrapper sets it on every score change
+ALTER TABLE cpu ADD COLUMN score_updated_at VARCHAR(19);
+ALTER TABLE gpu ADD COLUMN score_updated_at VARCHAR(19);
+UPDATE cpu SET score_updated_at = CURRENT_TIMESTAMP;
+UPDATE gpu SET score_updated_at = CURRENT_TIMESTAMP;
@@ -0,0 +1,7 @@
+-- Filled from cpu details page, NULL if page has no such metric
+ALTER TABLE cpu ADD COLUMN single_thread_score INTEGER;
+ALTER TABLE cpu ADD COLUMN tdp INTEGER;
+ALTER TABLE cpu ADD COLUMN cores INTEGER;
+ALTER TABLE cpu ADD COLUMN threads INTEGER;
+-- NULL until details page is loaded
+ALTER TABLE cpu ADD COLUMN metrics_updated_at VARCHAR(19);
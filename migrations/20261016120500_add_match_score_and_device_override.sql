@@ -0,0 +1,26 @@
+-- Confidence of fuzzy match in percent of ideal match, NULL until laptop is matched again
+ALTER TABLE laptop_model ADD COLUMN cpu_match_score INTEGER;
+ALTER TABLE laptop_model ADD COLUMN gpu_match_score INTEGER;
+-- Manual corrections of matching: pins laptop or any laptop, which composition is LIKE pattern,
+-- to given cpu and/or gpu. Laptop pin wins over pattern, newer pin wins over older
+CREATE TABLE IF NOT EXISTS laptop_device_override (
+    id INTEGER PRIMARY KEY NOT NULL,
+    laptop_id INTEGER,
+    composition_pattern VARCHAR(255),
+    cpu_id INTEGER,
+    gpu_id INTEGER,
+    CHECK (laptop_id IS NOT NULL OR composition_pattern IS NOT NULL),
+    CHECK (cpu_id IS NOT NULL OR gpu_id IS NOT NULL),
+    CONSTRAINT fk_laptop
+        FOREIGN KEY(laptop_id)
+        REFERENCES laptop_model(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_cpu
+        FOREIGN KEY(cpu_id)
+        REFERENCES cpu(id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_gpu
+        FOREIGN KEY(gpu_id)
+        REFERENCES gpu(id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,31 @@
+-- Progress of shop catalog walk, unfinished job is continued by `laptop_scrapper --resume`
+CREATE TABLE IF NOT EXISTS scrape_job (
+    id INTEGER PRIMARY KEY NOT NULL,
+    shop VARCHAR(50) NOT NULL,
+    -- Catalog url with filters and sorting
+    filter VARCHAR(1000) NOT NULL,
+    total_pages INTEGER,
+    started_at VARCHAR(19) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at VARCHAR(19)
+);
+CREATE TABLE IF NOT EXISTS scrape_job_page (
+    job_id INTEGER NOT NULL,
+    page INTEGER NOT NULL,
+    PRIMARY KEY (job_id, page),
+    CONSTRAINT fk_job
+        FOREIGN KEY(job_id)
+        REFERENCES scrape_job(id)
+        ON DELETE CASCADE
+);
+-- Products, which details couldn't be loaded or normalized, retried on resume until they fail
+-- too many times, e.g. product is removed from shop
+CREATE TABLE IF NOT EXISTS scrape_job_failure (
+    job_id INTEGER NOT NULL,
+    product_id INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    PRIMARY KEY (job_id, product_id),
+    CONSTRAINT fk_job
+        FOREIGN KEY(job_id)
+        REFERENCES scrape_job(id)
+        ON DELETE CASCADE
+);
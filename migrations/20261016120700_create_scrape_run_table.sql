@@ -0,0 +1,18 @@
+-- Every launch of scrapper, resumed job has several runs. Run is successful when it completes its job
+CREATE TABLE IF NOT EXISTS scrape_run (
+    id INTEGER PRIMARY KEY NOT NULL,
+    job_id INTEGER NOT NULL,
+    started_at VARCHAR(19) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at VARCHAR(19),
+    succeeded BOOLEAN NOT NULL DEFAULT FALSE,
+    pages INTEGER NOT NULL DEFAULT 0,
+    laptops INTEGER NOT NULL DEFAULT 0,
+    failed_products INTEGER NOT NULL DEFAULT 0,
+    CONSTRAINT fk_job
+        FOREIGN KEY(job_id)
+        REFERENCES scrape_job(id)
+        ON DELETE CASCADE
+);
+-- Shops and catalog filters are scraped by separate jobs, so every offer remembers its last run.
+-- NULL for offers saved before runs were recorded
+ALTER TABLE offer ADD COLUMN last_seen_run INTEGER;
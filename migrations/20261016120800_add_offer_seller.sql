@@ -0,0 +1,10 @@
+-- Seller of marketplace offer, NULL if it's unknown
+ALTER TABLE offer ADD COLUMN seller VARCHAR(255);
+-- Sold by third party seller, not by the shop itself
+ALTER TABLE offer ADD COLUMN third_party BOOLEAN NOT NULL DEFAULT FALSE;
+-- Availability doesn't depend on shop anymore: 'available', 'ends_soon' or 'out_of_stock'
+UPDATE offer SET availability = CASE availability
+    WHEN 'available' THEN 'available'
+    WHEN 'limited' THEN 'ends_soon'
+    ELSE 'out_of_stock'
+END;
@@ -1,20 +1,126 @@
-use laptop_selector::{connect, get_laptops, Error};
+use clap::Parser;
+use laptop_selector::{
+    connect, get_database_configuration, pareto_frontier, score_laptops, Error, LaptopPriorities,
+    LaptopSnapshot, Strategy,
+};
 use prettytable::{row, Table};
 use std::sync::Arc;
 
+#[derive(Parser, Default)]
+#[clap(version, about)]
+/// Prints laptops ranked the same way as laptop selector page
+struct Arguments {
+    #[clap(short, long)]
+    /// Database url [default: sqlite://laptops.db]
+    database_url: Option<String>,
+
+    #[clap(long, default_value_t = 100)]
+    /// CPU priority
+    cpu: i64,
+
+    #[clap(long, default_value_t = 0)]
+    /// GPU priority
+    gpu: i64,
+
+    #[clap(long, default_value_t = 0)]
+    /// CPU single thread priority
+    single_thread: i64,
+
+    #[clap(long, default_value_t = 0)]
+    /// CPU threads priority
+    threads: i64,
+
+    #[clap(long, default_value_t = 0)]
+    /// Low CPU TDP priority
+    low_tdp: i64,
+
+    #[clap(short, long, value_enum, default_value_t)]
+    /// Ranking strategy
+    strategy: Strategy,
+
+    #[clap(long)]
+    /// Maximal price, budget for performance-under-budget strategy
+    max_price: Option<i64>,
+
+    #[clap(long)]
+    /// Skip offers, which are out of stock
+    in_stock: bool,
+
+    #[clap(long)]
+    /// Skip offers, which are running out
+    hide_ends_soon: bool,
+
+    #[clap(long)]
+    /// Skip offers, which are sold by marketplace seller
+    shop_seller: bool,
+
+    #[clap(long)]
+    /// Also print laptops, which were not seen in the latest scrape
+    show_stale: bool,
+
+    #[clap(long)]
+    /// Print Pareto frontier over price, cpu and gpu scores instead of ranking
+    pareto: bool,
+}
+
+fn short_name(description: &str) -> &str {
+    description.split('/').next().unwrap().trim()
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Error> {
-    let pool = Arc::new(connect().await);
-    let mut laptops = get_laptops(pool).await?;
-    laptops.sort_by_key(|laptop| laptop.price * 1000 / (laptop.cpu_score + 1));
+    let arguments = Arguments::parse();
+    let database_settings = get_database_configuration(arguments.database_url)?;
+    let pool = Arc::new(connect(&database_settings).await?);
+    let snapshot = LaptopSnapshot::load(pool).await?;
+    if arguments.pareto {
+        let mut table = Table::new();
+        table.add_row(row!["Price", "CPU", "GPU", "Name", "Dominated by", "Url"]);
+        for marked in pareto_frontier(&snapshot.laptops)
+            .into_iter()
+            .filter(|marked| arguments.show_stale || !marked.laptop.stale)
+        {
+            table.add_row(row![
+                marked.laptop.price,
+                marked.laptop.cpu_score,
+                marked.laptop.gpu_score,
+                short_name(&marked.laptop.description),
+                marked
+                    .dominated_by
+                    .map(|laptop| short_name(&laptop.description))
+                    .unwrap_or_default(),
+                marked.laptop.url
+            ]);
+        }
+        table.printstd();
+        return Ok(());
+    }
+
+    let params = LaptopPriorities {
+        cpu: arguments.cpu,
+        gpu: arguments.gpu,
+        single_thread: arguments.single_thread,
+        threads: arguments.threads,
+        low_tdp: arguments.low_tdp,
+        quantity: usize::MAX,
+        strategy: arguments.strategy,
+        max_price: arguments.max_price,
+        in_stock: arguments.in_stock,
+        hide_ends_soon: arguments.hide_ends_soon,
+        shop_seller: arguments.shop_seller,
+        show_stale: arguments.show_stale,
+        ..Default::default()
+    };
     let mut table = Table::new();
-    table.add_row(row!["Score", "Price", "Name", "Url"]);
-    for laptop in laptops {
+    table.add_row(row!["Score", "CPU", "GPU", "Price", "Name", "Url"]);
+    for laptop in score_laptops(&snapshot, &params) {
         table.add_row(row![
-            laptop.cpu_score,
-            laptop.price,
-            laptop.description.split('/').next().unwrap().trim(),
-            laptop.url
+            laptop.total_score,
+            format!("{}%", laptop.cpu_percent),
+            format!("{}%", laptop.gpu_percent),
+            laptop.laptop.price,
+            short_name(&laptop.laptop.description),
+            laptop.laptop.url
         ]);
     }
     table.printstd();
@@ -0,0 +1,242 @@
+use laptop_selector::Error;
+use scraper::{Html, Selector};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+pub struct BenchmarkRow {
+    pub id: i64,
+    pub name: String,
+    pub url: String,
+    pub score: i64,
+}
+
+/// Parses passmark benchmark list page (cpu_list.php, gpu_list.php).
+/// Links in table lead to lookup pages, `lookup` pair replaces it with details page in url
+pub fn parse_benchmark_table(
+    html: &str,
+    rows: &str,
+    site: &str,
+    lookup: (&str, &str),
+) -> Result<Vec<BenchmarkRow>, Error> {
+    let document = Html::parse_document(html);
+    let row_selector = Selector::parse(rows).unwrap();
+    let cell_selector = Selector::parse("td").unwrap();
+    let link_selector = Selector::parse("a").unwrap();
+
+    let mut benchmarks = Vec::new();
+    for row in document.select(&row_selector) {
+        let cells = row.select(&cell_selector).collect::<Vec<_>>();
+        // avoid repeated header
+        if cells.len() < 2 {
+            continue;
+        }
+        let Some(link) = cells[0].select(&link_selector).next() else {
+            continue;
+        };
+        let href = link.value().attr("href").unwrap_or_default();
+        let url = format!("{site}{href}").replace(lookup.0, lookup.1);
+        let Some(id) = serde_urlencoded::from_str::<HashMap<String, String>>(href)?.remove("id")
+        else {
+            continue;
+        };
+        let id = id.parse()?;
+        let name = link.text().collect::<String>().trim().to_owned();
+        let score = cells[1]
+            .text()
+            .collect::<String>()
+            .replace(',', "")
+            .trim()
+            .parse()
+            .unwrap_or_default();
+        benchmarks.push(BenchmarkRow {
+            id,
+            name,
+            url,
+            score,
+        });
+    }
+    Ok(benchmarks)
+}
+
+/// Everything specific to one benchmark source, new source is added as one more descriptor
+pub struct BenchmarkKind {
+    /// Used in log messages
+    pub label: &'static str,
+    /// Table with `id, name, url, score, score_updated_at` columns, where devices are saved
+    pub table: &'static str,
+    /// Page with full list of devices
+    pub list_url: &'static str,
+    /// Css selector of device rows in the list, JS fills them after page is loaded
+    pub rows: &'static str,
+    /// Prefix of relative links in the list
+    pub site: &'static str,
+    /// Replace in links to get details page instead of lookup one
+    pub lookup: (&'static str, &'static str),
+    /// Saved list page in fixtures directory
+    pub fixture: &'static str,
+    /// Name of id 0 device, matched when nothing else fits
+    pub unknown: &'static str,
+}
+
+pub const CPU: BenchmarkKind = BenchmarkKind {
+    label: "CPU",
+    table: "cpu",
+    list_url: "https://www.cpubenchmark.net/cpu_list.php",
+    rows: "#cputable tbody tr",
+    site: "https://www.cpubenchmark.net/",
+    lookup: ("_lookup", ""),
+    fixture: "cpu_list.html",
+    unknown: "Unknown cpu",
+};
+
+pub const GPU: BenchmarkKind = BenchmarkKind {
+    label: "GPU",
+    table: "gpu",
+    list_url: "https://www.videocardbenchmark.net/gpu_list.php",
+    rows: "#cputable tbody tr",
+    site: "https://www.videocardbenchmark.net/",
+    lookup: ("video_lookup", "gpu"),
+    fixture: "gpu_list.html",
+    unknown: "Unknown gpu",
+};
+
+/// All benchmarks, which are loaded by scrapper
+pub const BENCHMARKS: &[BenchmarkKind] = &[CPU, GPU];
+
+impl BenchmarkKind {
+    pub fn parse(&self, html: &str) -> Result<Vec<BenchmarkRow>, Error> {
+        parse_benchmark_table(html, self.rows, self.site, self.lookup)
+    }
+
+    /// Whether any device is saved already
+    pub async fn is_saved(&self, pool: &SqlitePool) -> Result<bool, Error> {
+        let query = format!("SELECT EXISTS(SELECT 1 FROM {});", self.table);
+        Ok(sqlx::query_scalar::<_, bool>(&query)
+            .fetch_one(pool)
+            .await?)
+    }
+
+    /// Adds new devices and updates changed scores, safe to call on filled table
+    pub async fn save(&self, rows: &[BenchmarkRow], pool: &SqlitePool) -> Result<(), Error> {
+        // Table name can't be bound, it comes only from descriptors above
+        let unknown = format!(
+            "INSERT INTO {}(id, name, url, score, score_updated_at)
+                VALUES (0, $1, '', 0, CURRENT_TIMESTAMP)
+                ON CONFLICT(id) DO NOTHING",
+            self.table
+        );
+        sqlx::query(&unknown)
+            .bind(self.unknown)
+            .execute(pool)
+            .await?;
+        let upsert = format!(
+            "INSERT INTO {}(id, name, url, score, score_updated_at)
+                VALUES ($1, $2, $3, $4, CURRENT_TIMESTAMP)
+                ON CONFLICT(id) DO
+                UPDATE SET
+                    name=excluded.name,
+                    url=excluded.url,
+                    score=excluded.score,
+                    score_updated_at=CASE WHEN score = excluded.score
+                        THEN score_updated_at ELSE excluded.score_updated_at END",
+            self.table
+        );
+        for row in rows {
+            sqlx::query(&upsert)
+                .bind(row.id)
+                .bind(&row.name)
+                .bind(&row.url)
+                .bind(row.score)
+                .execute(pool)
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+/// Extra metrics from cpu details page (cpu.php?cpu=...&id=...)
+#[derive(Debug, Default)]
+pub struct CpuMetrics {
+    pub single_thread_score: Option<i64>,
+    pub tdp: Option<i64>,
+    pub cores: Option<i64>,
+    pub threads: Option<i64>,
+}
+
+/// Number right after `label`, thousands separators are skipped
+fn number_after(text: &str, label: &str) -> Option<i64> {
+    let (_, rest) = text.split_once(label)?;
+    rest.trim_start()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .parse()
+        .ok()
+}
+
+/// Hybrid cpus have "Total Cores: 12 Cores, 16 Threads", others "Cores: 4 Threads: 8"
+pub fn parse_cpu_details(html: &str) -> CpuMetrics {
+    let document = Html::parse_document(html);
+    let text = document
+        .root_element()
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    CpuMetrics {
+        single_thread_score: number_after(&text, "Single Thread Rating:"),
+        tdp: number_after(&text, "Typical TDP:"),
+        cores: number_after(&text, "Total Cores:").or_else(|| number_after(&text, "Cores:")),
+        threads: number_after(&text, "Threads:").or_else(|| number_after(&text, "Cores,")),
+    }
+}
+
+/// Cpus of saved laptops, which details page was not loaded yet, or all of them on `refresh`
+pub async fn cpus_without_metrics(
+    pool: &SqlitePool,
+    refresh: bool,
+) -> Result<Vec<(i64, String)>, Error> {
+    Ok(sqlx::query!(
+        "
+            SELECT id, url FROM cpu
+            WHERE id != 0 AND ($1 OR metrics_updated_at IS NULL)
+                AND id IN (SELECT cpu_id FROM laptop_model)
+            ORDER BY id ASC;
+        ",
+        refresh
+    )
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|cpu| (cpu.id, cpu.url))
+    .collect())
+}
+
+pub async fn save_cpu_metrics(
+    id: i64,
+    metrics: &CpuMetrics,
+    pool: &SqlitePool,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "
+            UPDATE cpu SET
+                single_thread_score = $1,
+                tdp = $2,
+                cores = $3,
+                threads = $4,
+                metrics_updated_at = CURRENT_TIMESTAMP
+            WHERE id = $5;
+        ",
+        metrics.single_thread_score,
+        metrics.tdp,
+        metrics.cores,
+        metrics.threads,
+        id
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
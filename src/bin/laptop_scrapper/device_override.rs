@@ -0,0 +1,85 @@
+use laptop_selector::Error;
+use sqlx::SqlitePool;
+
+/// Row of `laptop_device_override`: pins laptop or every laptop, which composition is LIKE
+/// pattern, to given cpu and/or gpu
+pub struct DeviceOverride {
+    pub id: i64,
+    pub laptop_id: Option<i64>,
+    pub composition_pattern: Option<String>,
+    pub cpu_id: Option<i64>,
+    pub gpu_id: Option<i64>,
+}
+
+impl DeviceOverride {
+    /// Saves new override, it wins over older ones of the same laptop or pattern
+    pub async fn add(
+        pool: &SqlitePool,
+        laptop_id: Option<i64>,
+        composition_pattern: Option<String>,
+        cpu_id: Option<i64>,
+        gpu_id: Option<i64>,
+    ) -> Result<Self, Error> {
+        let id = sqlx::query_scalar!(
+            "
+                INSERT INTO laptop_device_override(laptop_id, composition_pattern, cpu_id, gpu_id)
+                VALUES ($1, $2, $3, $4) RETURNING id;
+            ",
+            laptop_id,
+            composition_pattern,
+            cpu_id,
+            gpu_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(Self {
+            id,
+            laptop_id,
+            composition_pattern,
+            cpu_id,
+            gpu_id,
+        })
+    }
+
+    pub async fn list(pool: &SqlitePool) -> Result<Vec<Self>, Error> {
+        Ok(sqlx::query_as!(
+            Self,
+            r#"
+                SELECT id as "id!: i64", laptop_id, composition_pattern, cpu_id, gpu_id
+                FROM laptop_device_override
+                ORDER BY id ASC;
+            "#
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    /// Removes override, returns it if it existed
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<Option<Self>, Error> {
+        Ok(sqlx::query_as!(
+            Self,
+            r#"
+                DELETE FROM laptop_device_override WHERE id = $1
+                RETURNING id as "id!: i64", laptop_id, composition_pattern, cpu_id, gpu_id;
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?)
+    }
+
+    /// Ids of saved laptops, which matching is affected by the override
+    pub async fn laptop_ids(&self, pool: &SqlitePool) -> Result<Vec<i64>, Error> {
+        Ok(sqlx::query_scalar!(
+            r#"
+                SELECT id as "id!: i64" FROM laptop_model
+                WHERE id = $1 OR COALESCE(composition, '') LIKE $2
+                ORDER BY id ASC;
+            "#,
+            self.laptop_id,
+            self.composition_pattern
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+}
@@ -0,0 +1,215 @@
+use laptop_selector::Error;
+use sqlx::SqlitePool;
+use std::collections::HashSet;
+
+/// Failed product is not retried anymore after so many attempts, so the job can be completed
+pub const MAX_PRODUCT_ATTEMPTS: i64 = 3;
+
+/// One launch of scrapper, counts are saved when it's finished
+pub struct ScrapeRun {
+    pub id: i64,
+    pub pages: i64,
+    pub laptops: i64,
+    pub failed_products: i64,
+}
+
+impl ScrapeRun {
+    async fn start(pool: &SqlitePool, job_id: i64) -> Result<Self, Error> {
+        let id = sqlx::query_scalar!(
+            "INSERT INTO scrape_run(job_id) VALUES ($1) RETURNING id;",
+            job_id
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(Self {
+            id,
+            pages: 0,
+            laptops: 0,
+            failed_products: 0,
+        })
+    }
+}
+
+/// Persistent progress of walking shop catalog, so interrupted run can be continued
+pub struct ScrapeJob {
+    pub id: i64,
+    /// Known after the first page is loaded
+    pub total_pages: Option<i64>,
+    /// Current run, job can take several runs when it's resumed
+    pub run: ScrapeRun,
+}
+
+impl ScrapeJob {
+    pub async fn start(pool: &SqlitePool, shop: &str, filter: &str) -> Result<Self, Error> {
+        let id = sqlx::query_scalar!(
+            "INSERT INTO scrape_job(shop, filter) VALUES ($1, $2) RETURNING id;",
+            shop,
+            filter
+        )
+        .fetch_one(pool)
+        .await?;
+        Ok(Self {
+            id,
+            total_pages: None,
+            run: ScrapeRun::start(pool, id).await?,
+        })
+    }
+
+    /// The latest unfinished job with the same shop and filter
+    pub async fn last_unfinished(
+        pool: &SqlitePool,
+        shop: &str,
+        filter: &str,
+    ) -> Result<Option<Self>, Error> {
+        let Some(job) = sqlx::query!(
+            r#"
+                SELECT id as "id!: i64", total_pages
+                FROM scrape_job
+                WHERE shop = $1 AND filter = $2 AND finished_at IS NULL
+                ORDER BY id DESC
+                LIMIT 1;
+            "#,
+            shop,
+            filter
+        )
+        .fetch_optional(pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            id: job.id,
+            total_pages: job.total_pages,
+            run: ScrapeRun::start(pool, job.id).await?,
+        }))
+    }
+
+    pub async fn done_pages(&self, pool: &SqlitePool) -> Result<HashSet<u64>, Error> {
+        Ok(sqlx::query_scalar!(
+            "SELECT page FROM scrape_job_page WHERE job_id = $1;",
+            self.id
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|page| page as u64)
+        .collect())
+    }
+
+    pub async fn page_done(
+        &mut self,
+        pool: &SqlitePool,
+        page: u64,
+        total_pages: u64,
+    ) -> Result<(), Error> {
+        let (page, total_pages) = (page as i64, total_pages as i64);
+        sqlx::query!(
+            "INSERT OR IGNORE INTO scrape_job_page(job_id, page) VALUES ($1, $2);",
+            self.id,
+            page
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query!(
+            "UPDATE scrape_job SET total_pages = $1 WHERE id = $2;",
+            total_pages,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+        self.total_pages = Some(total_pages);
+        self.run.pages += 1;
+        Ok(())
+    }
+
+    /// Failed products, which are still retried
+    pub async fn failed_products(&self, pool: &SqlitePool) -> Result<Vec<i64>, Error> {
+        Ok(sqlx::query_scalar!(
+            "
+                SELECT product_id FROM scrape_job_failure
+                WHERE job_id = $1 AND attempts < $2
+                ORDER BY product_id;
+            ",
+            self.id,
+            MAX_PRODUCT_ATTEMPTS
+        )
+        .fetch_all(pool)
+        .await?)
+    }
+
+    pub async fn product_failed(
+        &mut self,
+        pool: &SqlitePool,
+        product_id: i64,
+    ) -> Result<(), Error> {
+        let attempts = sqlx::query_scalar!(
+            "
+                INSERT INTO scrape_job_failure(job_id, product_id) VALUES ($1, $2)
+                ON CONFLICT(job_id, product_id) DO UPDATE SET attempts = attempts + 1
+                RETURNING attempts;
+            ",
+            self.id,
+            product_id
+        )
+        .fetch_one(pool)
+        .await?;
+        if attempts >= MAX_PRODUCT_ATTEMPTS {
+            println!("Product {product_id} failed {attempts} times, it's not retried anymore");
+        }
+        self.run.failed_products += 1;
+        Ok(())
+    }
+
+    /// Forgets failure of the product and marks its offer as seen in current run
+    pub async fn product_saved(
+        &mut self,
+        pool: &SqlitePool,
+        shop: &str,
+        product_id: i64,
+    ) -> Result<(), Error> {
+        sqlx::query!(
+            "DELETE FROM scrape_job_failure WHERE job_id = $1 AND product_id = $2;",
+            self.id,
+            product_id
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query!(
+            "UPDATE offer SET last_seen_run = $1 WHERE shop = $2 AND product_id = $3;",
+            self.run.id,
+            shop,
+            product_id
+        )
+        .execute(pool)
+        .await?;
+        self.run.laptops += 1;
+        Ok(())
+    }
+
+    /// Saves counts of current run. Complete job, where every page is loaded and no failed product
+    /// is left to retry, is not resumed anymore and its run becomes successful
+    pub async fn finish(&self, pool: &SqlitePool, complete: bool) -> Result<(), Error> {
+        sqlx::query!(
+            "UPDATE scrape_run
+                SET finished_at = CURRENT_TIMESTAMP, succeeded = $1,
+                    pages = $2, laptops = $3, failed_products = $4
+                WHERE id = $5;",
+            complete,
+            self.run.pages,
+            self.run.laptops,
+            self.run.failed_products,
+            self.run.id
+        )
+        .execute(pool)
+        .await?;
+        if complete {
+            sqlx::query!(
+                "UPDATE scrape_job SET finished_at = CURRENT_TIMESTAMP WHERE id = $1;",
+                self.id
+            )
+            .execute(pool)
+            .await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,767 @@
+mod benchmark;
+mod device_override;
+mod job;
+mod rozetka;
+mod shop;
+#[cfg(test)]
+mod tests;
+
+use benchmark::{
+    cpus_without_metrics, parse_cpu_details, save_cpu_metrics, BenchmarkKind, BENCHMARKS,
+};
+use clap::{Parser, Subcommand};
+use device_override::DeviceOverride;
+use fantoccini::error::CmdError;
+use fantoccini::{ClientBuilder, Locator};
+use futures::{future::BoxFuture, FutureExt};
+use laptop_selector::{
+    connect, ensure_migrated, evaluate_matching, get_cpus, get_database_configuration, get_gpus,
+    get_price_drops, load_corpus, load_devices, open_read_only, DatabaseSettings, DeviceIndex,
+    DeviceKind, Error,
+};
+use rozetka::{FixtureFetcher, DEFAULT_URL};
+use serde::{Deserialize, Serialize};
+use shop::{scrape_shop, select_shop, HttpFetcher, WebDriverFetcher};
+use sqlx::{SqliteExecutor, SqlitePool};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+enum ParserType {
+    /// List page of benchmark site, kind describes where rows are saved
+    Benchmark(&'static BenchmarkKind),
+    /// Details page of cpu with given id, has metrics missing in the list
+    CpuDetails(i64),
+    /// Catalog of any supported shop, api calls are made from its page.
+    /// Optional api url overrides shop's default, bool parameter: resume the last unfinished job,
+    /// then cpu and gpu indices
+    ShopCatalog(Option<String>, bool, Arc<DeviceIndex>, Arc<DeviceIndex>),
+}
+
+/// Matched devices of laptop with match scores, see [`DeviceIndex::best_match`]
+struct DeviceMatch {
+    cpu_id: i64,
+    cpu_score: i64,
+    gpu_id: i64,
+    gpu_score: i64,
+}
+
+/// Best matching devices, unless they are pinned in `laptop_device_override`.
+/// Pins of `previous_model` are used too, it's the model offer belonged to before its key changed
+async fn match_devices(
+    pool: &SqlitePool,
+    model_key: &str,
+    previous_model: Option<i64>,
+    composition: &str,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<DeviceMatch, Error> {
+    let (cpu_index, cpu_score) = cpus.best_match(composition, DeviceKind::Cpu);
+    let (gpu_index, gpu_score) = gpus.best_match(composition, DeviceKind::Gpu);
+    let mut matched = DeviceMatch {
+        cpu_id: cpus.devices()[cpu_index].id,
+        cpu_score,
+        gpu_id: gpus.devices()[gpu_index].id,
+        gpu_score,
+    };
+
+    let pinned = sqlx::query!(
+        r#"
+            SELECT
+                (SELECT cpu_id FROM laptop_device_override
+                    WHERE cpu_id IS NOT NULL AND (
+                        laptop_id IN (SELECT id FROM laptop_model WHERE model_key = $1)
+                        OR laptop_id = $3 OR $2 LIKE composition_pattern
+                    )
+                    ORDER BY laptop_id IS NULL, id DESC LIMIT 1) as "cpu_id: i64",
+                (SELECT gpu_id FROM laptop_device_override
+                    WHERE gpu_id IS NOT NULL AND (
+                        laptop_id IN (SELECT id FROM laptop_model WHERE model_key = $1)
+                        OR laptop_id = $3 OR $2 LIKE composition_pattern
+                    )
+                    ORDER BY laptop_id IS NULL, id DESC LIMIT 1) as "gpu_id: i64";
+        "#,
+        model_key,
+        composition,
+        previous_model
+    )
+    .fetch_one(pool)
+    .await?;
+    if let Some(cpu_id) = pinned.cpu_id {
+        matched.cpu_id = cpu_id;
+        matched.cpu_score = 100;
+    }
+    if let Some(gpu_id) = pinned.gpu_id {
+        matched.gpu_id = gpu_id;
+        matched.gpu_score = 100;
+    }
+    Ok(matched)
+}
+
+async fn save_match(
+    executor: impl SqliteExecutor<'_>,
+    laptop_id: i64,
+    matched: &DeviceMatch,
+) -> Result<(), Error> {
+    sqlx::query!(
+        "
+            UPDATE laptop_model
+            SET cpu_id = $1, cpu_match_score = $2, gpu_id = $3, gpu_match_score = $4
+            WHERE id = $5;
+        ",
+        matched.cpu_id,
+        matched.cpu_score,
+        matched.gpu_id,
+        matched.gpu_score,
+        laptop_id
+    )
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Current time in format of `score_updated_at` columns
+async fn database_now(pool: &SqlitePool) -> Result<String, Error> {
+    Ok(
+        sqlx::query_scalar!(r#"SELECT CURRENT_TIMESTAMP as "now!: String";"#)
+            .fetch_one(pool)
+            .await?,
+    )
+}
+
+/// Matches again laptops, which cpu or gpu score was updated since `since`, also laptops with
+/// unknown device, as new devices could be added. Returns count of laptops with changed devices
+async fn rematch_refreshed_laptops(
+    pool: &Arc<SqlitePool>,
+    since: &str,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<usize, Error> {
+    let laptops = sqlx::query!(
+        "
+            SELECT laptop_model.id, laptop_model.model_key, laptop_model.composition,
+                laptop_model.cpu_id, laptop_model.gpu_id,
+                laptop_model.cpu_match_score, laptop_model.gpu_match_score
+            FROM laptop_model
+                JOIN cpu ON laptop_model.cpu_id = cpu.id
+                JOIN gpu ON laptop_model.gpu_id = gpu.id
+            WHERE cpu.score_updated_at >= $1 OR gpu.score_updated_at >= $1
+                OR laptop_model.cpu_id = 0 OR laptop_model.gpu_id = 0;
+        ",
+        since
+    )
+    .fetch_all(pool.as_ref())
+    .await?;
+    let mut rematched = 0;
+    for laptop in laptops {
+        let composition = laptop.composition.as_deref().unwrap_or_default();
+        let matched = match_devices(pool, &laptop.model_key, None, composition, cpus, gpus).await?;
+        if matched.cpu_id == laptop.cpu_id
+            && matched.gpu_id == laptop.gpu_id
+            && laptop.cpu_match_score == Some(matched.cpu_score)
+            && laptop.gpu_match_score == Some(matched.gpu_score)
+        {
+            continue;
+        }
+        save_match(pool.as_ref(), laptop.id, &matched).await?;
+        if matched.cpu_id != laptop.cpu_id || matched.gpu_id != laptop.gpu_id {
+            rematched += 1;
+        }
+    }
+    Ok(rematched)
+}
+
+fn device_name(devices: &DeviceIndex, id: i64) -> &str {
+    devices
+        .devices()
+        .iter()
+        .find(|device| device.id == id)
+        .map_or("?", |device| device.name.as_str())
+}
+
+/// Matches again every saved laptop (or only laptops with given ids) by its stored composition,
+/// prints changed devices and saves all changes in one transaction unless `dry_run`.
+/// Returns count of laptops with changed devices
+async fn rematch_laptops(
+    pool: &SqlitePool,
+    only: Option<&[i64]>,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+    dry_run: bool,
+) -> Result<usize, Error> {
+    let laptops = sqlx::query!(
+        "
+            SELECT id, model_key, description, composition, cpu_id, gpu_id,
+                cpu_match_score, gpu_match_score
+            FROM laptop_model
+            ORDER BY id ASC;
+        "
+    )
+    .fetch_all(pool)
+    .await?;
+    let mut changes = Vec::new();
+    let mut rematched = 0;
+    for laptop in laptops {
+        if only.is_some_and(|ids| !ids.contains(&laptop.id)) {
+            continue;
+        }
+        let composition = laptop.composition.as_deref().unwrap_or_default();
+        let matched = match_devices(pool, &laptop.model_key, None, composition, cpus, gpus).await?;
+        if matched.cpu_id != laptop.cpu_id || matched.gpu_id != laptop.gpu_id {
+            rematched += 1;
+            println!("{} {}", laptop.id, laptop.description);
+            if matched.cpu_id != laptop.cpu_id {
+                println!(
+                    "    cpu: {} -> {} ({}%)",
+                    device_name(cpus, laptop.cpu_id),
+                    device_name(cpus, matched.cpu_id),
+                    matched.cpu_score
+                );
+            }
+            if matched.gpu_id != laptop.gpu_id {
+                println!(
+                    "    gpu: {} -> {} ({}%)",
+                    device_name(gpus, laptop.gpu_id),
+                    device_name(gpus, matched.gpu_id),
+                    matched.gpu_score
+                );
+            }
+        } else if laptop.cpu_match_score == Some(matched.cpu_score)
+            && laptop.gpu_match_score == Some(matched.gpu_score)
+        {
+            continue;
+        }
+        changes.push((laptop.id, matched));
+    }
+    if !dry_run {
+        let mut transaction = pool.begin().await?;
+        for (laptop_id, matched) in &changes {
+            save_match(&mut *transaction, *laptop_id, matched).await?;
+        }
+        transaction.commit().await?;
+    }
+    Ok(rematched)
+}
+
+/// Benchmark table is filled by JS, wait until rows count stops changing
+async fn wait_benchmark_table(
+    client: &fantoccini::Client,
+    kind: &BenchmarkKind,
+) -> Result<(), CmdError> {
+    // At least check two times, to ensure JS loading is not active anymore
+    let mut row_count = 0;
+    let mut rows = client.find_all(Locator::Css(kind.rows)).await?;
+    while row_count == 0 || row_count != rows.len() {
+        row_count = rows.len();
+        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        rows = client.find_all(Locator::Css(kind.rows)).await?;
+    }
+    Ok(())
+}
+
+fn parse(
+    webdriver: String,
+    uri: String,
+    parser_type: ParserType,
+    pool: Arc<SqlitePool>,
+    semaphore: Arc<Semaphore>,
+) -> BoxFuture<'static, std::result::Result<(), Error>> {
+    async move {
+        let _permit = semaphore.acquire().await.unwrap();
+
+        // Open new window, and load page
+        let c = ClientBuilder::native()
+            // .connect("http://127.0.0.1:4444")    // gekodriver
+            .connect(&webdriver) // chromedriver
+            .await
+            .expect("failed to connect to WebDriver");
+        c.goto(&uri).await?;
+        c.maximize_window().await?;
+
+        match parser_type {
+            ParserType::Benchmark(kind) => {
+                wait_benchmark_table(&c, kind).await?;
+                let rows = kind.parse(&c.source().await?)?;
+                kind.save(&rows, &pool).await?;
+                println!("{} benchmarks dump complete", kind.label);
+            }
+            ParserType::CpuDetails(id) => {
+                let metrics = parse_cpu_details(&c.source().await?);
+                save_cpu_metrics(id, &metrics, &pool).await?;
+                println!("Loaded metrics of cpu {id}: {metrics:?}");
+            }
+            ParserType::ShopCatalog(api_url, resume, cpus, gpus) => {
+                let fetcher = WebDriverFetcher { client: &c };
+                let shop = select_shop(&uri, api_url, &fetcher)?;
+                scrape_shop(shop.as_ref(), &uri, resume, &pool, &cpus, &gpus).await?;
+            }
+        }
+
+        c.close_window().await?;
+        c.close().await?;
+        Ok(())
+    }
+    .map(|result| {
+        if result.is_err() {
+            println!("{result:#?}");
+        }
+        result
+    })
+    .boxed()
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WebDriverSettings {
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for WebDriverSettings {
+    fn default() -> Self {
+        Self {
+            host: String::from("127.0.0.1"),
+            port: 9515,
+        }
+    }
+}
+
+impl WebDriverSettings {
+    fn connection_url(self) -> String {
+        format!("http://{}:{}", self.host, self.port)
+    }
+}
+
+pub fn get_configuration() -> Result<WebDriverSettings, config::ConfigError> {
+    config::Config::builder()
+        .add_source(config::Config::try_from(&WebDriverSettings::default()).unwrap())
+        .add_source(config::File::with_name("webdriver.yaml").required(false))
+        .add_source(
+            config::Environment::with_prefix("LAPTOP_SCRAPPER")
+                .try_parsing(true)
+                .separator("_"),
+        )
+        .build()?
+        .try_deserialize()
+}
+
+/// Benchmark devices, which labelled corpus is matched with
+enum DeviceSource {
+    /// Directory with cpu.json and gpu.json
+    Files(PathBuf),
+    /// cpu and gpu tables of existing database
+    Database(DatabaseSettings),
+}
+
+impl DeviceSource {
+    /// Cpu and gpu indices
+    async fn load(self) -> Result<(DeviceIndex, DeviceIndex), Error> {
+        match self {
+            DeviceSource::Files(dir) => Ok((
+                DeviceIndex::new(load_devices(&dir.join("cpu.json"))?),
+                DeviceIndex::new(load_devices(&dir.join("gpu.json"))?),
+            )),
+            DeviceSource::Database(settings) => {
+                // Evaluation doesn't create or migrate the database,
+                // missing one has every migration pending
+                let pool = open_read_only(&settings).await?;
+                ensure_migrated(pool.as_ref()).await?;
+                let pool = Arc::new(pool.expect("migrated database exists"));
+                Ok((
+                    DeviceIndex::new(get_cpus(pool.clone()).await?),
+                    DeviceIndex::new(get_gpus(pool).await?),
+                ))
+            }
+        }
+    }
+}
+
+/// Precision, recall and mismatches of cpu and gpu matching on labelled corpus
+async fn print_matching_evaluation(corpus: &Path, devices: DeviceSource) -> Result<(), Error> {
+    let corpus = load_corpus(corpus)?;
+    let (cpus, gpus) = devices.load().await?;
+    for (label, kind, devices) in [
+        ("cpu", DeviceKind::Cpu, &cpus),
+        ("gpu", DeviceKind::Gpu, &gpus),
+    ] {
+        let report = evaluate_matching(&corpus, kind, devices);
+        println!(
+            "{label}: precision {:.1}% ({}/{}), recall {:.1}% ({}/{}) on {} compositions",
+            report.precision() * 100.0,
+            report.correct,
+            report.predicted,
+            report.recall() * 100.0,
+            report.correct,
+            report.expected,
+            report.samples
+        );
+        for mismatch in &report.mismatches {
+            println!(
+                "  expected {}, matched {} ({}%): {}",
+                mismatch.expected.as_deref().unwrap_or("unknown"),
+                mismatch.matched.as_deref().unwrap_or("unknown"),
+                mismatch.confidence,
+                mismatch.composition
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_override(device_override: &DeviceOverride, cpus: &DeviceIndex, gpus: &DeviceIndex) {
+    let pattern = device_override
+        .composition_pattern
+        .as_deref()
+        .unwrap_or_default();
+    let target = match device_override.laptop_id {
+        Some(laptop_id) => format!("laptop {laptop_id}"),
+        None => format!("composition LIKE {pattern:?}"),
+    };
+    println!("{} {target}", device_override.id);
+    if let Some(cpu_id) = device_override.cpu_id {
+        println!("    cpu: {}", device_name(cpus, cpu_id));
+    }
+    if let Some(gpu_id) = device_override.gpu_id {
+        println!("    gpu: {}", device_name(gpus, gpu_id));
+    }
+}
+
+async fn manage_overrides(
+    command: OverrideCommand,
+    pool: &SqlitePool,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<(), Error> {
+    let device_override = match command {
+        OverrideCommand::Add {
+            laptop,
+            pattern,
+            cpu,
+            gpu,
+        } => DeviceOverride::add(pool, laptop, pattern, cpu, gpu).await?,
+        OverrideCommand::List => {
+            for device_override in DeviceOverride::list(pool).await? {
+                print_override(&device_override, cpus, gpus);
+            }
+            return Ok(());
+        }
+        OverrideCommand::Delete { id } => {
+            let Some(device_override) = DeviceOverride::delete(pool, id).await? else {
+                println!("No override {id}");
+                return Ok(());
+            };
+            device_override
+        }
+    };
+    print_override(&device_override, cpus, gpus);
+    let laptop_ids = device_override.laptop_ids(pool).await?;
+    let rematched = rematch_laptops(pool, Some(&laptop_ids), cpus, gpus, false).await?;
+    println!("Rematched {rematched} laptops");
+    Ok(())
+}
+
+#[derive(Subcommand)]
+enum OverrideCommand {
+    /// Pins devices of laptop or of laptops with matching composition and matches them again
+    Add {
+        #[clap(long, required_unless_present = "pattern", conflicts_with = "pattern")]
+        /// Id of laptop
+        laptop: Option<i64>,
+
+        #[clap(long)]
+        /// SQL LIKE pattern of composition, e.g. "%Ryzen 5 7535HS%"
+        pattern: Option<String>,
+
+        #[clap(long, required_unless_present = "gpu")]
+        /// Id of cpu in benchmark table
+        cpu: Option<i64>,
+
+        #[clap(long)]
+        /// Id of gpu in benchmark table
+        gpu: Option<i64>,
+    },
+    /// Prints saved overrides
+    List,
+    /// Removes override and matches its laptops again
+    Delete {
+        /// Id of override, as printed by list
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Matches again cpu and gpu of every saved laptop from stored composition, without scraping
+    Rematch {
+        #[clap(long)]
+        /// Only print changed matches, don't save them
+        dry_run: bool,
+    },
+    /// Matches labelled compositions and reports precision, recall and mismatches
+    EvalMatching {
+        #[clap(long, default_value = "fixtures/matching/corpus.json")]
+        /// Json array of {"composition", "cpu", "gpu"}, null device should stay unknown
+        corpus: PathBuf,
+
+        #[clap(long, default_value = "fixtures/matching")]
+        /// Directory with cpu.json and gpu.json, the corpus is labelled with their names
+        devices: PathBuf,
+
+        #[clap(long, conflicts_with = "devices")]
+        /// Use cpu and gpu tables of database instead of --devices, database is opened read only
+        from_database: bool,
+    },
+    /// Manual corrections of matched cpu and gpu, applied to saved laptops immediately
+    Override {
+        #[clap(subcommand)]
+        command: OverrideCommand,
+    },
+}
+
+#[derive(Parser, Default)]
+#[clap(version, about)]
+/// Saves data from benchmark sites and rosetka to database
+struct Arguments {
+    #[clap(short, long)]
+    /// Url with laptop list of supported shop (rozetka), can contain filters or sorting  [default: https://rozetka.com.ua/ua/notebooks/c80004/]
+    url: Option<String>,
+
+    #[clap(short, long)]
+    /// Database url [default: sqlite://laptops.db]
+    database_url: Option<String>,
+
+    #[clap(long)]
+    /// Directory with saved cpu_list.html, gpu_list.html, cpu_<id>.html, get_<page>.json and details*.json,
+    /// used instead of live sites
+    from_fixtures: Option<PathBuf>,
+
+    #[clap(long)]
+    /// Call catalog api and load cpu details pages directly instead of through browser
+    /// (WebDriver is still needed for empty benchmark tables)
+    headless: bool,
+
+    #[clap(long)]
+    /// Shop api url, overrides shop's default, e.g. to use local stub server
+    api_url: Option<String>,
+
+    #[clap(long)]
+    /// Load benchmarks even if they are already saved: update scores, add new devices,
+    /// match again laptops, which devices were changed, and reload cpu details
+    refresh_benchmarks: bool,
+
+    #[clap(long)]
+    /// Continue the last interrupted scraping of the same catalog url: retry failed products
+    /// and load only pages, which were not loaded yet
+    resume: bool,
+
+    #[clap(long)]
+    /// After scraping print laptops, which price dropped more than given percent since last run
+    report_drops: Option<i64>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+/// Feeds saved pages and api responses through the same parsers as live scraping
+async fn scrape_fixtures(
+    dir: &Path,
+    url: &str,
+    refresh_benchmarks: bool,
+    resume: bool,
+    pool: &Arc<SqlitePool>,
+) -> Result<(), Error> {
+    let refresh_started = database_now(pool).await?;
+    for kind in BENCHMARKS {
+        if refresh_benchmarks || !kind.is_saved(pool).await? {
+            let rows = kind.parse(&std::fs::read_to_string(dir.join(kind.fixture))?)?;
+            kind.save(&rows, pool).await?;
+        }
+    }
+    let cpus = Arc::new(DeviceIndex::new(get_cpus(pool.clone()).await?));
+    let gpus = Arc::new(DeviceIndex::new(get_gpus(pool.clone()).await?));
+    if refresh_benchmarks {
+        let rematched = rematch_refreshed_laptops(pool, &refresh_started, &cpus, &gpus).await?;
+        println!("Rematched {rematched} laptops");
+    }
+    let fetcher = FixtureFetcher {
+        dir: dir.to_owned(),
+    };
+    let shop = select_shop(url, None, &fetcher)?;
+    scrape_shop(shop.as_ref(), url, resume, pool, &cpus, &gpus).await?;
+
+    for (id, _) in cpus_without_metrics(pool, refresh_benchmarks).await? {
+        let path = dir.join(format!("cpu_{id}.html"));
+        if path.exists() {
+            let metrics = parse_cpu_details(&std::fs::read_to_string(path)?);
+            save_cpu_metrics(id, &metrics, pool).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn scrape_live(
+    url: &str,
+    api_url: Option<String>,
+    headless: bool,
+    refresh_benchmarks: bool,
+    resume: bool,
+    pool: &Arc<SqlitePool>,
+) -> Result<(), Error> {
+    let webdriver_url = get_configuration()?.connection_url();
+    let semaphore = Arc::new(Semaphore::new(10));
+    let refresh_started = database_now(pool).await?;
+
+    let mut set = tokio::task::JoinSet::new();
+
+    for kind in BENCHMARKS {
+        if refresh_benchmarks || !kind.is_saved(pool).await? {
+            set.spawn(parse(
+                webdriver_url.clone(),
+                String::from(kind.list_url),
+                ParserType::Benchmark(kind),
+                pool.clone(),
+                semaphore.clone(),
+            ));
+        }
+    }
+
+    while let Some(result) = set.join_next().await {
+        if result.is_err() {
+            println!("{result:#?}");
+        }
+    }
+
+    // All data is saved to database
+    let cpus = Arc::new(DeviceIndex::new(get_cpus(pool.clone()).await?));
+    let gpus = Arc::new(DeviceIndex::new(get_gpus(pool.clone()).await?));
+
+    if refresh_benchmarks {
+        let rematched = rematch_refreshed_laptops(pool, &refresh_started, &cpus, &gpus).await?;
+        println!("Rematched {rematched} laptops");
+    }
+
+    if headless {
+        let fetcher = HttpFetcher::new()?;
+        let shop = select_shop(url, api_url, &fetcher)?;
+        scrape_shop(shop.as_ref(), url, resume, pool, &cpus, &gpus).await?;
+    } else {
+        set.spawn(parse(
+            webdriver_url.clone(),
+            url.to_owned(),
+            ParserType::ShopCatalog(api_url, resume, cpus, gpus),
+            pool.clone(),
+            semaphore.clone(),
+        ));
+
+        if let Err(err) = set.join_next().await.transpose() {
+            println!("{err:#?}");
+        };
+    }
+
+    // Only cpus of saved laptops are needed, so it goes after shop
+    let cpus_without_metrics = cpus_without_metrics(pool, refresh_benchmarks).await?;
+    if headless {
+        // Details pages are static, unlike benchmark tables
+        let fetcher = HttpFetcher::new()?;
+        for (id, url) in cpus_without_metrics {
+            match fetcher.fetch_page(&url).await {
+                Ok(html) => {
+                    let metrics = parse_cpu_details(&html);
+                    save_cpu_metrics(id, &metrics, pool).await?;
+                    println!("Loaded metrics of cpu {id}: {metrics:?}");
+                }
+                Err(err) => println!("Cpu {id} details failed: {err:#?}"),
+            }
+        }
+    } else {
+        for (id, url) in cpus_without_metrics {
+            set.spawn(parse(
+                webdriver_url.clone(),
+                url,
+                ParserType::CpuDetails(id),
+                pool.clone(),
+                semaphore.clone(),
+            ));
+        }
+    }
+
+    while let Some(result) = set.join_next().await {
+        if result.is_err() {
+            println!("{result:#?}");
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let arguments = Arguments::parse();
+    if let Some(Command::EvalMatching {
+        corpus,
+        devices,
+        from_database,
+    }) = arguments.command
+    {
+        let devices = if from_database {
+            DeviceSource::Database(get_database_configuration(arguments.database_url)?)
+        } else {
+            DeviceSource::Files(devices)
+        };
+        return print_matching_evaluation(&corpus, devices).await;
+    }
+
+    let database_settings = get_database_configuration(arguments.database_url.clone())?;
+    let pool = Arc::new(connect(&database_settings).await?);
+
+    if let Some(Command::Rematch { dry_run }) = arguments.command {
+        let cpus = DeviceIndex::new(get_cpus(pool.clone()).await?);
+        let gpus = DeviceIndex::new(get_gpus(pool.clone()).await?);
+        let rematched = rematch_laptops(&pool, None, &cpus, &gpus, dry_run).await?;
+        if dry_run {
+            println!("{rematched} laptops would be rematched");
+        } else {
+            println!("Rematched {rematched} laptops");
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Override { command }) = arguments.command {
+        let cpus = DeviceIndex::new(get_cpus(pool.clone()).await?);
+        let gpus = DeviceIndex::new(get_gpus(pool.clone()).await?);
+        return manage_overrides(command, &pool, &cpus, &gpus).await;
+    }
+
+    let url = arguments.url.unwrap_or(String::from(DEFAULT_URL));
+
+    if let Some(dir) = &arguments.from_fixtures {
+        scrape_fixtures(
+            dir,
+            &url,
+            arguments.refresh_benchmarks,
+            arguments.resume,
+            &pool,
+        )
+        .await?;
+    } else {
+        scrape_live(
+            &url,
+            arguments.api_url,
+            arguments.headless,
+            arguments.refresh_benchmarks,
+            arguments.resume,
+            &pool,
+        )
+        .await?;
+    }
+
+    if let Some(percent) = arguments.report_drops {
+        for drop in get_price_drops(pool, percent).await? {
+            println!(
+                "{} -> {}: {} {} {}",
+                drop.previous_price, drop.price, drop.shop, drop.description, drop.url
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use crate::shop::{model_key, CatalogFetcher, CatalogPage, LaptopRecord, Shop};
+use futures::{future::BoxFuture, FutureExt};
+use laptop_selector::Error;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+pub const HOSTS: &[&str] = &["rozetka.com.ua", "www.rozetka.com.ua"];
+
+pub const DEFAULT_URL: &str = "https://rozetka.com.ua/ua/notebooks/c80004/";
+
+pub const DEFAULT_API_URL: &str = "https://xl-catalog-api.rozetka.com.ua/v4/goods/";
+
+/// Answers catalog api requests with responses saved to directory: `get_<page>.json`
+/// for list pages, products for `getDetails` are picked by `product_ids` from every `details*.json`
+pub struct FixtureFetcher {
+    pub dir: PathBuf,
+}
+
+impl FixtureFetcher {
+    fn read(&self, url: &str) -> Result<Value, Error> {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let endpoint = path.rsplit('/').next().unwrap_or_default();
+        let parameters = serde_urlencoded::from_str::<HashMap<String, String>>(query)?;
+        if endpoint == "getDetails" {
+            let ids = parameters
+                .get("product_ids")
+                .map(String::as_str)
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|id| id.parse().ok())
+                .collect::<HashSet<i64>>();
+            let mut products = Vec::new();
+            for entry in std::fs::read_dir(&self.dir)? {
+                let path = entry?.path();
+                let is_details = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("details") && name.ends_with(".json"));
+                if !is_details {
+                    continue;
+                }
+                let mut response: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                if let Value::Array(data) = response["data"].take() {
+                    products.extend(data.into_iter().filter(|product| {
+                        product["id"].as_i64().is_some_and(|id| ids.contains(&id))
+                    }));
+                }
+            }
+            Ok(json!({ "data": products }))
+        } else {
+            let page = parameters.get("page").map(String::as_str).unwrap_or("1");
+            let path = self.dir.join(format!("{endpoint}_{page}.json"));
+            Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+        }
+    }
+}
+
+impl CatalogFetcher for FixtureFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Value, Error>> {
+        async move { self.read(url) }.boxed()
+    }
+}
+
+/// Body of every catalog api response
+#[derive(Deserialize)]
+pub struct Response<T> {
+    pub data: T,
+}
+
+/// `get` payload: one page of product ids
+#[derive(Deserialize)]
+pub struct GoodsPage {
+    pub ids: Vec<i64>,
+    #[serde(default)]
+    pub total_pages: u64,
+}
+
+/// One product of `getDetails` payload
+#[derive(Deserialize, Debug)]
+pub struct GoodsDetails {
+    pub id: i64,
+    pub title: String,
+    pub price: i64,
+    pub href: String,
+    #[serde(default)]
+    pub docket: Option<Docket>,
+    #[serde(default)]
+    pub image_main: Option<String>,
+    /// "available", "limited", "out_of_stock", ...
+    #[serde(default)]
+    pub sell_status: Option<String>,
+    /// Marketplace seller, rozetka itself for own goods
+    #[serde(default)]
+    pub seller: Option<Seller>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Seller {
+    pub title: String,
+}
+
+/// Shop independent availability of rozetka `sell_status`, missing status means available
+fn availability(sell_status: Option<&str>) -> &'static str {
+    match sell_status {
+        None | Some("available") => "available",
+        // shown as "ends soon" on product page
+        Some("limited") => "ends_soon",
+        Some(_) => "out_of_stock",
+    }
+}
+
+/// Short characteristics of product, comes either as plain string or as list of groups
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Docket {
+    Text(String),
+    Items(Vec<DocketItem>),
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DocketItem {
+    #[serde(default)]
+    pub value_title: Option<String>,
+}
+
+impl From<GoodsDetails> for LaptopRecord {
+    fn from(details: GoodsDetails) -> Self {
+        let composition = match details.docket {
+            Some(Docket::Text(text)) => Some(text),
+            Some(Docket::Items(items)) => {
+                let title = items.into_iter().next().and_then(|item| item.value_title);
+                if title.is_none() {
+                    println!("Docket has no value title in product {}", details.id);
+                }
+                title
+            }
+            None if details.title.chars().filter(|&c| c == '/').count() >= 3 => {
+                Some(details.title.clone())
+            }
+            None => {
+                println!("Title has no description in product {}", details.id);
+                None
+            }
+        };
+        let image = details.image_main.unwrap_or_default();
+
+        if composition.is_none() || image.is_empty() {
+            println!("Not full info in product {}", details.id);
+        }
+
+        LaptopRecord {
+            id: details.id,
+            shop: "rozetka",
+            model: model_key(&details.title),
+            description: details.title,
+            price: details.price,
+            url: details.href,
+            composition,
+            image,
+            availability: String::from(availability(details.sell_status.as_deref())),
+            third_party: details
+                .seller
+                .as_ref()
+                .is_some_and(|seller| !seller.title.eq_ignore_ascii_case("rozetka")),
+            seller: details.seller.map(|seller| seller.title),
+        }
+    }
+}
+
+pub struct Rozetka<'a> {
+    api_url: String,
+    filter: String,
+    fetcher: &'a dyn CatalogFetcher,
+}
+
+impl<'a> Rozetka<'a> {
+    pub fn new(url: &str, api_url: Option<String>, fetcher: &'a dyn CatalogFetcher) -> Self {
+        Self {
+            api_url: api_url.unwrap_or(String::from(DEFAULT_API_URL)),
+            filter: pageurl_to_filter(url),
+            fetcher,
+        }
+    }
+}
+
+impl Shop for Rozetka<'_> {
+    fn name(&self) -> &'static str {
+        "rozetka"
+    }
+
+    fn list_page(&self, number: u64) -> BoxFuture<'_, Result<CatalogPage, Error>> {
+        async move {
+            let request = format!(
+                "{}get?{}",
+                self.api_url,
+                self.filter.replace("page_number", &number.to_string())
+            );
+            let page =
+                serde_json::from_value::<Response<GoodsPage>>(self.fetcher.fetch(&request).await?)?
+                    .data;
+            Ok(CatalogPage {
+                ids: page.ids,
+                total_pages: page.total_pages,
+            })
+        }
+        .boxed()
+    }
+
+    fn fetch_details<'a>(&'a self, ids: &'a [i64]) -> BoxFuture<'a, Result<Vec<Value>, Error>> {
+        async move {
+            let ids = ids.iter().map(i64::to_string).collect::<Vec<_>>().join(",");
+            let request = format!("{}getDetails?country=UA&lang=ua&with_groups=1&with_docket=1&goods_group_href=1&product_ids={ids}", self.api_url);
+            Ok(serde_json::from_value::<Response<Vec<Value>>>(self.fetcher.fetch(&request).await?)?.data)
+        }
+        .boxed()
+    }
+
+    fn normalize(&self, product: Value) -> Result<LaptopRecord, Error> {
+        let id = product["id"].to_string();
+        serde_json::from_value::<GoodsDetails>(product)
+            .map(LaptopRecord::from)
+            .map_err(|source| Error::ProductSchema { id, source })
+    }
+}
+
+pub fn pageurl_to_filter<'a>(url: &'a str) -> String {
+    let mut parameters_iterator = url.split('/').skip(5);
+    let mut parameters: HashMap<&'a str, &'a str> = HashMap::from([
+        ("front-type", "xl"),
+        ("county", "UA"),
+        ("lang", "ua"),
+        ("page", "page_number"),
+    ]);
+    if let Some(category) = parameters_iterator.next() {
+        parameters.insert("category_id", &category[1..]);
+    }
+    if let Some(other_parameters) = parameters_iterator.next() {
+        parameters.extend(other_parameters.split(';').filter_map(|param| {
+            let mut iter = param.split('=');
+            if let (Some(name), Some(value)) = (iter.next(), iter.next()) {
+                Some((name, value))
+            } else {
+                None
+            }
+        }));
+    }
+    let mut parameters_iterator = parameters.iter();
+    let (&name, &value) = parameters_iterator.next().unwrap();
+    parameters_iterator.fold(format!("{name}={value}"), |acc, (&name, &value)| {
+        acc + "&" + name + "=" + value
+    })
+}
@@ -0,0 +1,407 @@
+use crate::job::ScrapeJob;
+use crate::match_devices;
+use crate::rozetka::{self, Rozetka};
+use futures::{future::BoxFuture, FutureExt};
+use laptop_selector::{record_price, DeviceIndex, Error};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+use url::Url;
+
+const DATA_FETCHER: &str = r#"
+    const [url, callback] = arguments;
+    fetch(url)
+    .then(data => {
+        callback(data.json())
+    })
+"#;
+
+/// Transport for shop api requests, returns response body
+pub trait CatalogFetcher: Sync {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Value, Error>>;
+}
+
+/// Runs requests from opened shop page, so they look like ordinary browser requests
+pub struct WebDriverFetcher<'a> {
+    pub client: &'a fantoccini::Client,
+}
+
+impl CatalogFetcher for WebDriverFetcher<'_> {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Value, Error>> {
+        async move {
+            Ok(self
+                .client
+                .execute_async(DATA_FETCHER, vec![json!(url)])
+                .await?)
+        }
+        .boxed()
+    }
+}
+
+/// Calls shop api and loads static pages directly, no browser needed
+pub struct HttpFetcher {
+    client: reqwest::Client,
+}
+
+impl HttpFetcher {
+    pub fn new() -> Result<Self, Error> {
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/118.0 Safari/537.36")
+            .build()?;
+        Ok(Self { client })
+    }
+
+    async fn get(&self, url: &str, accept: &str) -> Result<String, Error> {
+        Ok(self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, accept)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+
+    /// Html of static page, which doesn't need JS to be filled
+    pub async fn fetch_page(&self, url: &str) -> Result<String, Error> {
+        self.get(url, "text/html").await
+    }
+}
+
+impl CatalogFetcher for HttpFetcher {
+    fn fetch<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<Value, Error>> {
+        async move {
+            let body = self.get(url, "application/json").await?;
+            Ok(serde_json::from_str(&body)?)
+        }
+        .boxed()
+    }
+}
+
+/// Product ids listed on one catalog page
+pub struct CatalogPage {
+    pub ids: Vec<i64>,
+    pub total_pages: u64,
+}
+
+/// Laptop normalized from any shop
+pub struct LaptopRecord {
+    /// Product id in shop
+    pub id: i64,
+    pub shop: &'static str,
+    /// Same for the laptop in every shop, see [`model_key`]
+    pub model: String,
+    pub description: String,
+    pub price: i64,
+    pub url: String,
+    /// `None` if shop doesn't show it, then saved composition is kept
+    pub composition: Option<String>,
+    pub image: String,
+    /// "available", "ends_soon" or "out_of_stock"
+    pub availability: String,
+    /// Marketplace seller, `None` if shop doesn't tell
+    pub seller: Option<String>,
+    /// Sold by somebody else than the shop itself
+    pub third_party: bool,
+}
+
+/// Screen resolution like "1920x1080", also written with cyrillic "х" or "×"
+fn is_resolution(token: &str) -> bool {
+    let sides = token.split(['x', 'X', 'х', 'Х', '×']).collect::<Vec<_>>();
+    sides.len() == 2
+        && sides
+            .iter()
+            .all(|side| !side.is_empty() && side.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Normalized part number from title like "Ноутбук ASUS TUF Gaming F15 FX507ZC4-HN009 (90NR0GW1-M000P0) Mecha Gray",
+/// the first parenthesised token, which looks like it. Composition after " / " and resolution are
+/// not part numbers, whole title is used if there is no part number in it
+pub fn model_key(title: &str) -> String {
+    let name = title.split(" / ").next().unwrap_or(title);
+    let part_number = name
+        .split('(')
+        .skip(1)
+        .filter_map(|part| part.split_once(')').map(|(inside, _)| inside.trim()))
+        .find(|inside| {
+            inside.len() >= 5
+                && !inside.contains(char::is_whitespace)
+                && inside.contains(|c: char| c.is_ascii_digit())
+                && inside.contains(|c: char| c.is_ascii_alphabetic())
+                && !is_resolution(inside)
+        });
+    part_number
+        .unwrap_or(title)
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Source of laptops: walks catalog pages, loads products details and normalizes them
+pub trait Shop: Send + Sync {
+    /// Stored in `offer.shop`
+    fn name(&self) -> &'static str;
+
+    /// Product ids on catalog page `number` (starting from 1) and total pages count
+    fn list_page(&self, number: u64) -> BoxFuture<'_, Result<CatalogPage, Error>>;
+
+    /// Raw details of products with given ids
+    fn fetch_details<'a>(&'a self, ids: &'a [i64]) -> BoxFuture<'a, Result<Vec<Value>, Error>>;
+
+    /// Converts raw product details into laptop record
+    fn normalize(&self, product: Value) -> Result<LaptopRecord, Error>;
+}
+
+/// Chooses shop by host of catalog url, `api_url` overrides shop's default api location
+pub fn select_shop<'a>(
+    url: &str,
+    api_url: Option<String>,
+    fetcher: &'a dyn CatalogFetcher,
+) -> Result<Box<dyn Shop + 'a>, Error> {
+    let host = Url::parse(url)?.host_str().unwrap_or_default().to_owned();
+    if rozetka::HOSTS.contains(&host.as_str()) {
+        Ok(Box::new(Rozetka::new(url, api_url, fetcher)))
+    } else {
+        Err(Error::UnsupportedShop(host))
+    }
+}
+
+/// Saves laptop model and offer of the shop, composition of model is kept if the shop has none.
+/// Title and image of model come from its first saved offer, other offers can be named differently.
+/// When offer moves to a model with another key, device overrides of its old model move with it
+pub async fn save_laptop(
+    laptop: &LaptopRecord,
+    pool: &SqlitePool,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<(), Error> {
+    let composition = laptop.composition.as_deref().unwrap_or_default();
+    let previous_model = sqlx::query_scalar!(
+        "
+            SELECT offer.model_id FROM offer
+                JOIN laptop_model ON offer.model_id = laptop_model.id
+            WHERE offer.shop = $1 AND offer.product_id = $2 AND laptop_model.model_key != $3;
+        ",
+        laptop.shop,
+        laptop.id,
+        laptop.model
+    )
+    .fetch_optional(pool)
+    .await?;
+    let matched =
+        match_devices(pool, &laptop.model, previous_model, composition, cpus, gpus).await?;
+
+    let model_id = sqlx::query_scalar!(
+        "INSERT INTO laptop_model(
+                model_key,
+                image,
+                description,
+                composition,
+                cpu_id,
+                cpu_match_score,
+                gpu_id,
+                gpu_match_score
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT(model_key) DO
+            UPDATE SET
+                image=CASE WHEN image = '' THEN excluded.image ELSE image END,
+                composition=COALESCE(excluded.composition, composition),
+                cpu_id=CASE WHEN excluded.composition IS NULL
+                    THEN cpu_id ELSE excluded.cpu_id END,
+                cpu_match_score=CASE WHEN excluded.composition IS NULL
+                    THEN cpu_match_score ELSE excluded.cpu_match_score END,
+                gpu_id=CASE WHEN excluded.composition IS NULL
+                    THEN gpu_id ELSE excluded.gpu_id END,
+                gpu_match_score=CASE WHEN excluded.composition IS NULL
+                    THEN gpu_match_score ELSE excluded.gpu_match_score END
+            RETURNING id;
+            ",
+        laptop.model,
+        laptop.image,
+        laptop.description,
+        laptop.composition,
+        matched.cpu_id,
+        matched.cpu_score,
+        matched.gpu_id,
+        matched.gpu_score
+    )
+    .fetch_one(pool)
+    .await?;
+
+    let offer_id = sqlx::query_scalar!(
+        "INSERT INTO offer(
+                model_id,
+                shop,
+                product_id,
+                url,
+                price,
+                availability,
+                seller,
+                third_party
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT(shop, product_id) DO
+            UPDATE SET
+                model_id=excluded.model_id,
+                url=excluded.url,
+                price=excluded.price,
+                availability=excluded.availability,
+                seller=excluded.seller,
+                third_party=excluded.third_party
+            RETURNING id;
+            ",
+        model_id,
+        laptop.shop,
+        laptop.id,
+        laptop.url,
+        laptop.price,
+        laptop.availability,
+        laptop.seller,
+        laptop.third_party
+    )
+    .fetch_one(pool)
+    .await?;
+    if let Some(previous_model) = previous_model {
+        sqlx::query!(
+            "UPDATE laptop_device_override SET laptop_id = $1 WHERE laptop_id = $2;",
+            model_id,
+            previous_model
+        )
+        .execute(pool)
+        .await?;
+    }
+    record_price(pool, offer_id, laptop.price).await
+}
+
+/// Offers could move to another model, when its key is changed. Device overrides are moved
+/// together with them by [`save_laptop`]
+pub async fn remove_models_without_offers(pool: &SqlitePool) -> Result<(), Error> {
+    sqlx::query!("DELETE FROM laptop_model WHERE id NOT IN (SELECT model_id FROM offer);")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Loads details of products and saves them, products which failed to load or normalize
+/// are remembered in job
+async fn save_products(
+    ids: &[i64],
+    shop: &dyn Shop,
+    job: &mut ScrapeJob,
+    pool: &SqlitePool,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<(), Error> {
+    let mut failed = ids.to_vec();
+    for product in shop.fetch_details(ids).await? {
+        // Skip only broken product, others on the page are still fine
+        match shop.normalize(product) {
+            Ok(laptop) => {
+                save_laptop(&laptop, pool, cpus, gpus).await?;
+                job.product_saved(pool, laptop.shop, laptop.id).await?;
+                failed.retain(|id| *id != laptop.id);
+            }
+            Err(err) => println!("{err}"),
+        }
+    }
+    for id in failed {
+        job.product_failed(pool, id).await?;
+    }
+    Ok(())
+}
+
+/// Loads one catalog page with details of every laptop on it, returns total pages count
+async fn process_page(
+    number: u64,
+    shop: &dyn Shop,
+    job: &mut ScrapeJob,
+    pool: &SqlitePool,
+    cpus: &DeviceIndex,
+    gpus: &DeviceIndex,
+) -> Result<u64, Error> {
+    println!("Parsing {} page {number}", shop.name());
+    let page = shop.list_page(number).await?;
+    save_products(&page.ids, shop, job, pool, cpus, gpus).await?;
+    job.page_done(pool, number, page.total_pages).await?;
+
+    Ok(page.total_pages)
+}
+
+/// Retries failed products and loads pages, which are not loaded yet by the job.
+/// Returns whether the job is complete
+async fn walk_job(
+    shop: &dyn Shop,
+    job: &mut ScrapeJob,
+    pool: &Arc<SqlitePool>,
+    cpus: &Arc<DeviceIndex>,
+    gpus: &Arc<DeviceIndex>,
+) -> Result<bool, Error> {
+    let failed = job.failed_products(pool).await?;
+    if !failed.is_empty() {
+        println!("Retrying {} failed products", failed.len());
+        if let Err(err) = save_products(&failed, shop, job, pool, cpus, gpus).await {
+            println!("Retry failed: {err:#?}");
+        }
+    }
+
+    let done_pages = job.done_pages(pool).await?;
+    let total_pages = match job.total_pages {
+        // Total is saved with the first loaded page, which is always the first one
+        Some(total_pages) => total_pages as u64,
+        None => process_page(1, shop, job, pool, cpus, gpus).await?,
+    };
+    let mut complete = true;
+    for i in (2..=total_pages).filter(|i| !done_pages.contains(i)) {
+        if let Err(err) = process_page(i, shop, job, pool, cpus, gpus).await {
+            println!("Page {i} failed: {err:#?}");
+            complete = false;
+        }
+    }
+    Ok(complete && job.failed_products(pool).await?.is_empty())
+}
+
+/// Walks all pages of shop catalog, `filter` is catalog url. With `resume` the last unfinished
+/// job of the same catalog is continued: failed products are retried and only not loaded pages are walked.
+/// Run is finished even if scraping fails, then it's not successful
+pub async fn scrape_shop(
+    shop: &dyn Shop,
+    filter: &str,
+    resume: bool,
+    pool: &Arc<SqlitePool>,
+    cpus: &Arc<DeviceIndex>,
+    gpus: &Arc<DeviceIndex>,
+) -> Result<(), Error> {
+    let unfinished = if resume {
+        ScrapeJob::last_unfinished(pool, shop.name(), filter).await?
+    } else {
+        None
+    };
+    let mut job = match unfinished {
+        Some(job) => {
+            println!("Resuming scrape job {}", job.id);
+            job
+        }
+        None => ScrapeJob::start(pool, shop.name(), filter).await?,
+    };
+
+    let complete = match walk_job(shop, &mut job, pool, cpus, gpus).await {
+        Ok(complete) => complete,
+        Err(err) => {
+            job.finish(pool, false).await?;
+            return Err(err);
+        }
+    };
+    job.finish(pool, complete).await?;
+    println!(
+        "Scrape run {}: {} pages, {} laptops, {} failed products",
+        job.run.id, job.run.pages, job.run.laptops, job.run.failed_products
+    );
+    if !complete {
+        println!(
+            "Scrape job {} is not complete, continue it with --resume",
+            job.id
+        );
+    }
+    remove_models_without_offers(pool).await
+}
@@ -0,0 +1,189 @@
+//! Saved pages and api responses from fixtures/scrape go through the same parsers as live ones
+//! and laptops are grouped into models by the same keys
+
+use crate::benchmark::{parse_cpu_details, CPU, GPU};
+use crate::rozetka::{FixtureFetcher, DEFAULT_URL};
+use crate::shop::{model_key, select_shop, CatalogFetcher, HttpFetcher, LaptopRecord};
+use axum::http::{StatusCode, Uri};
+use axum::response::{IntoResponse, Json};
+use axum::Router;
+use laptop_selector::Error;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/scrape")
+}
+
+fn read(name: &str) -> String {
+    std::fs::read_to_string(fixtures().join(name)).unwrap()
+}
+
+#[test]
+fn cpu_list_rows() {
+    let rows = CPU.parse(&read(CPU.fixture)).unwrap();
+    // repeated header row in the middle of table is skipped
+    assert_eq!(rows.len(), 7);
+    let row = &rows[0];
+    assert_eq!(row.id, 4747);
+    assert_eq!(row.name, "Intel Core i5-12450H");
+    assert_eq!(row.score, 16548);
+    assert_eq!(
+        row.url,
+        "https://www.cpubenchmark.net/cpu.php?cpu=Intel+Core+i5-12450H&id=4747"
+    );
+}
+
+#[test]
+fn gpu_list_rows() {
+    let rows = GPU.parse(&read(GPU.fixture)).unwrap();
+    assert_eq!(rows.len(), 8);
+    let row = &rows[0];
+    assert_eq!(row.id, 4417);
+    assert_eq!(row.name, "GeForce RTX 3050 Laptop GPU");
+    assert_eq!(row.score, 9895);
+    assert_eq!(
+        row.url,
+        "https://www.videocardbenchmark.net/gpu.php?gpu=GeForce+RTX+3050+Laptop+GPU&id=4417"
+    );
+}
+
+#[test]
+fn cpu_details_metrics() {
+    let metrics = parse_cpu_details(&read("cpu_4747.html"));
+    assert_eq!(metrics.single_thread_score, Some(3391));
+    assert_eq!(metrics.tdp, Some(45));
+    assert_eq!(metrics.cores, Some(8));
+    assert_eq!(metrics.threads, Some(12));
+
+    let metrics = parse_cpu_details(&read("cpu_4850.html"));
+    assert_eq!(metrics.single_thread_score, Some(3068));
+    assert_eq!(metrics.tdp, Some(15));
+    assert_eq!(metrics.cores, Some(6));
+    assert_eq!(metrics.threads, Some(8));
+}
+
+/// Local catalog api answering from fixtures, request without saved response gets 404.
+/// Returns api url for `--api-url`
+fn spawn_stub_api() -> String {
+    let fixtures = Arc::new(FixtureFetcher { dir: fixtures() });
+    let router = Router::new().fallback(move |uri: Uri| async move {
+        match fixtures.fetch(&uri.to_string()).await {
+            Ok(response) => Json(response).into_response(),
+            Err(_) => StatusCode::NOT_FOUND.into_response(),
+        }
+    });
+    let listener = TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(router.into_make_service());
+    tokio::spawn(server);
+    format!("http://{address}/v4/goods/")
+}
+
+/// Every product of catalog page normalized by rozetka shop
+async fn rozetka_page(number: u64) -> (Vec<i64>, u64, Vec<LaptopRecord>) {
+    let fetcher = FixtureFetcher { dir: fixtures() };
+    let shop = select_shop(DEFAULT_URL, None, &fetcher).unwrap();
+    let page = shop.list_page(number).await.unwrap();
+    let laptops = shop
+        .fetch_details(&page.ids)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|product| shop.normalize(product).unwrap())
+        .collect();
+    (page.ids, page.total_pages, laptops)
+}
+
+#[tokio::test]
+async fn rozetka_first_page() {
+    let (ids, total_pages, laptops) = rozetka_page(1).await;
+    assert_eq!(ids, [380000001, 380000002, 380000003]);
+    assert_eq!(total_pages, 2);
+    assert_eq!(laptops.len(), 3);
+
+    let laptop = laptops
+        .iter()
+        .find(|laptop| laptop.id == 380000001)
+        .unwrap();
+    assert_eq!(laptop.shop, "rozetka");
+    assert_eq!(laptop.model, "90NR0GW1M000P0");
+    assert_eq!(laptop.price, 36999);
+    assert_eq!(
+        laptop.url,
+        "https://rozetka.com.ua/ua/asus-90nr0gw1-m000p0/p380000001/"
+    );
+    assert!(laptop
+        .composition
+        .as_deref()
+        .is_some_and(|composition| composition.contains("Intel Core i5-12450H")));
+    assert!(!laptop.image.is_empty());
+    assert_eq!(laptop.availability, "available");
+}
+
+#[tokio::test]
+async fn rozetka_second_page() {
+    let (ids, _, laptops) = rozetka_page(2).await;
+    assert_eq!(ids, [380000004, 380000005, 380000006]);
+
+    // Another offer of the first page laptop, running out
+    let limited = laptops
+        .iter()
+        .find(|laptop| laptop.id == 380000006)
+        .unwrap();
+    assert_eq!(limited.model, "90NR0GW1M000P0");
+    assert_eq!(limited.availability, "ends_soon");
+    assert_eq!(limited.seller.as_deref(), Some("Rozetka"));
+    assert!(!limited.third_party);
+
+    let marketplace = laptops
+        .iter()
+        .find(|laptop| laptop.id == 380000005)
+        .unwrap();
+    assert_eq!(marketplace.seller.as_deref(), Some("ComputerShop"));
+    assert!(marketplace.third_party);
+}
+
+#[test]
+fn model_key_is_part_number() {
+    assert_eq!(
+        model_key("Ноутбук ASUS TUF Gaming F15 FX507ZC4-HN009 (90NR0GW1-M000P0) Mecha Gray"),
+        "90NR0GW1M000P0"
+    );
+    assert_eq!(
+        model_key("Ноутбук Acer (1920x1080) Nitro V 15 (NH.QNBEU.002) / Екран (2560x1600)"),
+        "NHQNBEU002"
+    );
+}
+
+#[test]
+fn model_key_skips_resolution() {
+    let first = model_key("Ноутбук Lenovo V15 G4 / 15.6\" IPS (1920x1080) / Intel Core i3-1315U");
+    let second = model_key("Ноутбук HP 255 G10 15.6\" (1920х1080) / AMD Ryzen 5 7530U (2.0 ГГц)");
+    assert_ne!(first, second);
+    assert_ne!(first, "1920X1080");
+    // no part number, whole title is the key
+    assert!(second.starts_with("НОУТБУКHP255G10"));
+}
+
+#[tokio::test]
+async fn http_fetcher_on_stub_api() {
+    let api_url = spawn_stub_api();
+    let fetcher = HttpFetcher::new().unwrap();
+    let shop = select_shop(DEFAULT_URL, Some(api_url), &fetcher).unwrap();
+
+    let page = shop.list_page(2).await.unwrap();
+    assert_eq!(page.ids, [380000004, 380000005, 380000006]);
+    assert_eq!(page.total_pages, 2);
+    let products = shop.fetch_details(&page.ids).await.unwrap();
+    assert_eq!(products.len(), 3);
+
+    // There is no third page
+    let missing = shop.list_page(3).await;
+    assert!(
+        matches!(missing, Err(Error::Http(err)) if err.status() == Some(StatusCode::NOT_FOUND))
+    );
+}
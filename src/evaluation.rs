@@ -0,0 +1,102 @@
+use crate::{Cpu, DeviceIndex, DeviceKind, Error};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Composition with devices, which should be matched from it, `None` if the device is not
+/// in the benchmark tables and should stay unknown
+#[derive(Deserialize)]
+pub struct LabelledComposition {
+    pub composition: String,
+    pub cpu: Option<String>,
+    pub gpu: Option<String>,
+}
+
+impl LabelledComposition {
+    pub fn expected(&self, kind: DeviceKind) -> Option<&str> {
+        match kind {
+            DeviceKind::Cpu => self.cpu.as_deref(),
+            DeviceKind::Gpu => self.gpu.as_deref(),
+        }
+    }
+}
+
+/// Corpus is json array of `{"composition": "...", "cpu": "...", "gpu": null}`
+pub fn load_corpus(path: &Path) -> Result<Vec<LabelledComposition>, Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Devices dumped from cpu or gpu table as json array, names trimmed like in [`crate::get_cpus`]
+pub fn load_devices(path: &Path) -> Result<Vec<Cpu>, Error> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+pub struct Mismatch {
+    pub composition: String,
+    pub expected: Option<String>,
+    /// `None` if composition was matched to unknown device
+    pub matched: Option<String>,
+    pub confidence: i64,
+}
+
+#[derive(Default)]
+pub struct MatchingReport {
+    pub samples: usize,
+    /// Compositions matched to some known device
+    pub predicted: usize,
+    /// Compositions labelled with some device
+    pub expected: usize,
+    /// Compositions matched to the labelled device
+    pub correct: usize,
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl MatchingReport {
+    /// Part of known matches, which are right
+    pub fn precision(&self) -> f64 {
+        ratio(self.correct, self.predicted)
+    }
+
+    /// Part of labelled devices, which are found
+    pub fn recall(&self) -> f64 {
+        ratio(self.correct, self.expected)
+    }
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+    if whole == 0 {
+        1.0
+    } else {
+        part as f64 / whole as f64
+    }
+}
+
+/// Matches every composition of corpus like scrapper does and compares with labels,
+/// device with id 0 is the unknown one
+pub fn evaluate_matching(
+    corpus: &[LabelledComposition],
+    kind: DeviceKind,
+    devices: &DeviceIndex,
+) -> MatchingReport {
+    let mut report = MatchingReport::default();
+    for sample in corpus {
+        let expected = sample.expected(kind);
+        let (index, confidence) = devices.best_match(&sample.composition, kind);
+        let device = &devices.devices()[index];
+        let matched = (device.id != 0).then_some(device.name.as_str());
+
+        report.samples += 1;
+        report.predicted += usize::from(matched.is_some());
+        report.expected += usize::from(expected.is_some());
+        if matched.is_some() && matched == expected {
+            report.correct += 1;
+        } else if matched != expected {
+            report.mismatches.push(Mismatch {
+                composition: sample.composition.clone(),
+                expected: expected.map(str::to_owned),
+                matched: matched.map(str::to_owned),
+                confidence,
+            });
+        }
+    }
+    report
+}
@@ -1,38 +1,176 @@
-use axum::{response::Html, routing::post, Extension, Router};
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    response::Html,
+    routing::{get, post},
+    Extension, Json, Router,
+};
 use fantoccini::error::CmdError;
 use minijinja::render;
-use serde::{Deserialize, Serialize};
-use sqlx::{migrate::MigrateDatabase, Sqlite, SqlitePool};
-use std::sync::Arc;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use sqlx::{
+    migrate::{Migrate, MigrateDatabase},
+    sqlite::SqliteConnectOptions,
+    Sqlite, SqlitePool,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    str::FromStr,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant, SystemTime},
+};
 
-const DB_URL: &str = "sqlite://laptops.db";
+mod evaluation;
+mod matching;
+mod pareto;
+mod price_history;
+mod scoring;
+mod vendor;
 
-pub async fn connect() -> SqlitePool {
-    if Sqlite::database_exists(DB_URL).await.unwrap_or(false) {
-        SqlitePool::connect(DB_URL).await.unwrap()
-    } else {
-        println!("Creating database {DB_URL}");
-        Sqlite::create_database(DB_URL)
-            .await
-            .expect("database creation error");
+pub use evaluation::{
+    evaluate_matching, load_corpus, load_devices, LabelledComposition, MatchingReport, Mismatch,
+};
+pub use matching::{
+    composition_devices, extract_device, fuzzy_best_match, get_best_match, DeviceIndex, DeviceKind,
+};
+pub use pareto::{dominates, pareto_frontier, ParetoLaptop};
+pub use price_history::{
+    get_min_price, get_price_drops, get_price_histories, get_price_history, price_chart,
+    record_price, PriceDrop, PricePoint,
+};
+pub use scoring::{
+    ParetoOptimal, PerformanceUnderBudget, RawPerformance, Scorer, Strategy, ValueForMoney,
+};
+pub use vendor::{cpu_vendor, gpu_vendor, is_integrated_gpu};
+
+#[derive(Deserialize, Serialize)]
+pub struct DatabaseSettings {
+    pub url: String,
+}
+
+impl Default for DatabaseSettings {
+    fn default() -> Self {
+        Self {
+            url: String::from("sqlite://laptops.db"),
+        }
+    }
+}
+
+/// Resolves database settings: defaults, then `database.yaml`, then `DATABASE_URL`
+/// environment variable (also read from `.env`), then url passed from command line
+pub fn get_database_configuration(
+    url: Option<String>,
+) -> Result<DatabaseSettings, config::ConfigError> {
+    // DATABASE_URL from .env is used like the one from environment, which wins over .env
+    dotenvy::dotenv().ok();
+    config::Config::builder()
+        .add_source(config::Config::try_from(&DatabaseSettings::default())?)
+        .add_source(config::File::with_name("database.yaml").required(false))
+        .add_source(
+            config::Environment::with_prefix("DATABASE")
+                .try_parsing(true)
+                .separator("_"),
+        )
+        .set_override_option("url", url)?
+        .build()?
+        .try_deserialize()
+}
+
+/// Opens database, creating empty one if it doesn't exist, without applying migrations
+pub async fn open(settings: &DatabaseSettings) -> Result<SqlitePool, Error> {
+    let url = settings.url.as_str();
+    if !Sqlite::database_exists(url).await.unwrap_or(false) {
+        println!("Creating database {url}");
+        Sqlite::create_database(url).await?;
+    }
+    Ok(SqlitePool::connect(url).await?)
+}
+
+/// Opens existing database read only, `None` if it doesn't exist yet
+pub async fn open_read_only(settings: &DatabaseSettings) -> Result<Option<SqlitePool>, Error> {
+    let url = settings.url.as_str();
+    if !Sqlite::database_exists(url).await.unwrap_or(false) {
+        return Ok(None);
+    }
+    let options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(false)
+        .read_only(true);
+    Ok(Some(SqlitePool::connect_with(options).await?))
+}
+
+/// Opens database and applies all pending migrations
+pub async fn connect(settings: &DatabaseSettings) -> Result<SqlitePool, Error> {
+    let db = open(settings).await?;
+    sqlx::migrate!().run(&db).await?;
+    Ok(db)
+}
+
+#[derive(Debug)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    pub applied: bool,
+}
+
+/// Lists all known migrations and whether they are applied to database,
+/// every migration is pending if there is no database (`None`) or it was never migrated.
+/// Database is not changed
+pub async fn get_migrations_status(
+    pool: Option<&SqlitePool>,
+) -> Result<Vec<MigrationStatus>, Error> {
+    let mut applied = Vec::new();
+    if let Some(pool) = pool {
+        let migrated = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!: i64" FROM sqlite_master
+                WHERE type = 'table' AND name = '_sqlx_migrations';"#
+        )
+        .fetch_one(pool)
+        .await?;
+        if migrated > 0 {
+            applied = pool.acquire().await?.list_applied_migrations().await?;
+        }
+    }
+    Ok(sqlx::migrate!()
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied: applied
+                .iter()
+                .any(|applied| applied.version == migration.version),
+        })
+        .collect())
+}
 
-        let db = SqlitePool::connect(DB_URL)
-            .await
-            .expect("database connection error");
-        sqlx::migrate!()
-            .run(&db)
-            .await
-            .expect("tables creation error");
-        db
+/// Fails with [`Error::PendingMigrations`] if database schema is behind
+pub async fn ensure_migrated(pool: Option<&SqlitePool>) -> Result<(), Error> {
+    let pending = get_migrations_status(pool)
+        .await?
+        .into_iter()
+        .filter(|migration| !migration.applied)
+        .map(|migration| migration.version)
+        .collect::<Vec<_>>();
+    if pending.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::PendingMigrations(pending))
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct Cpu {
     pub id: i64,
     pub name: String,
     pub url: String,
     pub score: i64,
+    /// When benchmark site was last seen with different score
+    pub score_updated_at: Option<String>,
+    /// Metrics from details page, always `None` for gpus
+    pub single_thread_score: Option<i64>,
+    pub tdp: Option<i64>,
+    pub cores: Option<i64>,
+    pub threads: Option<i64>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -54,13 +192,42 @@ pub enum Error {
 
     #[error("Parse JSON error occured: {0}")]
     JsonError(#[from] serde_json::Error),
+
+    #[error("Unexpected schema of product {id}: {source}")]
+    ProductSchema {
+        id: String,
+        source: serde_json::Error,
+    },
+
+    #[error("Unsupported shop: {0}")]
+    UnsupportedShop(String),
+
+    #[error("Url parse error occured: {0}")]
+    UrlParse(#[from] url::ParseError),
+
+    #[error("HTTP error occured: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Server error occured: {0}")]
+    Server(#[from] hyper::Error),
+
+    #[error("IO error occured: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Migration error occured: {0}")]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+
+    #[error("Database schema is behind, pending migrations: {0:?}")]
+    PendingMigrations(Vec<i64>),
 }
 
 pub async fn get_cpus(pool: Arc<SqlitePool>) -> Result<Vec<Cpu>, Error> {
     let mut from_base = sqlx::query_as!(
         Cpu,
         "
-            SELECT * FROM cpu ORDER BY id ASC;
+            SELECT id, name, url, score, score_updated_at,
+                single_thread_score, tdp, cores, threads
+            FROM cpu ORDER BY id ASC;
         "
     )
     .fetch_all(pool.as_ref())
@@ -74,9 +241,12 @@ pub async fn get_cpus(pool: Arc<SqlitePool>) -> Result<Vec<Cpu>, Error> {
 pub async fn get_gpus(pool: Arc<SqlitePool>) -> Result<Vec<Cpu>, Error> {
     let mut from_base = sqlx::query_as!(
         Cpu,
-        "
-            SELECT * FROM gpu ORDER BY id ASC;
-        "
+        r#"
+            SELECT id, name, url, score, score_updated_at,
+                NULL as "single_thread_score: i64", NULL as "tdp: i64",
+                NULL as "cores: i64", NULL as "threads: i64"
+            FROM gpu ORDER BY id ASC;
+        "#
     )
     .fetch_all(pool.as_ref())
     .await?;
@@ -86,36 +256,127 @@ pub async fn get_gpus(pool: Arc<SqlitePool>) -> Result<Vec<Cpu>, Error> {
     Ok(from_base)
 }
 
-#[derive(PartialEq, Serialize)]
+/// Device matches with lower score are flagged on the page for manual check
+pub const LOW_CONFIDENCE_MATCH: i64 = 60;
+
+/// Laptop model with one of its offers
+#[derive(Clone, PartialEq, Serialize)]
 pub struct LaptopView {
     pub id: i64,
+    /// Offer, which price history is shown
+    pub offer_id: i64,
+    pub shop: String,
     pub image: String,
     pub description: String,
     pub composition: Option<String>,
     pub url: String,
     pub price: i64,
+    /// of the cheapest offer: "available", "ends_soon" or "out_of_stock"
+    pub availability: String,
+    pub seller: Option<String>,
+    pub third_party: bool,
+    pub offer_count: i64,
     pub cpu_id: i64,
     pub gpu_id: i64,
     pub cpu_score: i64,
     pub gpu_score: i64,
+    /// 0 if unknown, as scores above
+    pub cpu_single_thread_score: i64,
+    pub cpu_tdp: Option<i64>,
+    pub cpu_cores: Option<i64>,
+    pub cpu_threads: Option<i64>,
+    /// Confidence of device matching in percent, `None` if laptop was not matched since it was added
+    pub cpu_match_score: Option<i64>,
+    pub gpu_match_score: Option<i64>,
     /// for debug fuzzy comparison  purposes
     pub cpu_name: String,
     pub gpu_name: String,
+    /// Offer is not seen in the latest successful scrape of its shop, probably gone from shop
+    pub stale: bool,
 }
 
+/// Laptop models with their cheapest offer, unavailable or stale offer is taken only if there is
+/// no other, so laptop is stale when all its offers are
 pub async fn get_laptops(pool: Arc<SqlitePool>) -> Result<Vec<LaptopView>, Error> {
+    let mut laptops = get_laptop_offers(pool).await?;
+    laptops.dedup_by_key(|laptop| laptop.id);
+    Ok(laptops)
+}
+
+/// Laptop models with each of their offers. Offers of laptop go one after another, preferred one
+/// goes first: cheapest, unavailable or stale offer only after all others. Offer is stale, when
+/// it's not seen by any run of the latest successful scrape job of its shop and catalog filter
+pub async fn get_laptop_offers(pool: Arc<SqlitePool>) -> Result<Vec<LaptopView>, Error> {
     Ok(sqlx::query_as!(
         LaptopView,
-        "
-            SELECT laptop.id, laptop.image, laptop.description, 
-                laptop.composition, laptop.url, laptop.price, 
-                laptop.cpu_id, laptop.gpu_id,
+        r#"
+            WITH offer_state AS (
+                SELECT offer.*,
+                    COALESCE(offer.last_seen_run, 0) < COALESCE((
+                        SELECT MIN(run.id) FROM scrape_run run
+                        WHERE run.job_id = (
+                            SELECT latest.id FROM scrape_job latest
+                                JOIN scrape_run succeeded ON succeeded.job_id = latest.id
+                            WHERE succeeded.succeeded AND latest.shop = offer.shop
+                                -- offer, which was never seen by a run, is compared with any filter
+                                AND latest.filter = COALESCE((
+                                    SELECT seen.filter FROM scrape_run seen_run
+                                        JOIN scrape_job seen ON seen.id = seen_run.job_id
+                                    WHERE seen_run.id = offer.last_seen_run
+                                ), latest.filter)
+                            ORDER BY succeeded.id DESC
+                            LIMIT 1
+                        )
+                    ), 0) as stale
+                FROM offer
+            )
+            SELECT laptop_model.id, offer.id as offer_id, offer.shop, laptop_model.image,
+                laptop_model.description, laptop_model.composition, offer.url, offer.price,
+                offer.availability, offer.seller, offer.third_party as "third_party!: bool",
+                (SELECT COUNT(*) FROM offer counted WHERE counted.model_id = laptop_model.id)
+                    as "offer_count!: i64",
+                laptop_model.cpu_id, laptop_model.gpu_id,
                 cpu.score as cpu_score, gpu.score as gpu_score,
-                cpu.name as cpu_name, gpu.name as gpu_name 
-            FROM laptop
-                JOIN cpu ON laptop.cpu_id = cpu.id
-                JOIN gpu on laptop.gpu_id = gpu.id;
-        "
+                COALESCE(cpu.single_thread_score, 0) as "cpu_single_thread_score!: i64",
+                cpu.tdp as cpu_tdp, cpu.cores as cpu_cores, cpu.threads as cpu_threads,
+                laptop_model.cpu_match_score, laptop_model.gpu_match_score,
+                cpu.name as cpu_name, gpu.name as gpu_name,
+                offer.stale as "stale!: bool"
+            FROM laptop_model
+                JOIN offer_state offer ON offer.model_id = laptop_model.id
+                JOIN cpu ON laptop_model.cpu_id = cpu.id
+                JOIN gpu on laptop_model.gpu_id = gpu.id
+            ORDER BY laptop_model.id, offer.stale, offer.availability = 'out_of_stock',
+                offer.price ASC;
+        "#
+    )
+    .fetch_all(pool.as_ref())
+    .await?)
+}
+
+#[derive(Serialize)]
+pub struct Offer {
+    pub id: i64,
+    pub shop: String,
+    pub url: String,
+    pub price: i64,
+    pub availability: String,
+    pub seller: Option<String>,
+    pub third_party: bool,
+}
+
+/// Offers of laptop model in every shop, cheapest first
+pub async fn get_offers(pool: Arc<SqlitePool>, laptop_id: i64) -> Result<Vec<Offer>, Error> {
+    Ok(sqlx::query_as!(
+        Offer,
+        r#"
+            SELECT id, shop, url, price, availability, seller,
+                third_party as "third_party!: bool"
+            FROM offer
+            WHERE model_id = $1
+            ORDER BY availability = 'out_of_stock', price ASC;
+        "#,
+        laptop_id
     )
     .fetch_all(pool.as_ref())
     .await?)
@@ -142,6 +403,54 @@ const PAGE_TEMPLATE: &str = r#"
         <p> GPU priority: </p>
         <div><input type="range" id="gpu" name="gpu" min="0" max="1000" value="{{param.gpu}}" /></div>
 
+        <p> CPU single thread priority: </p>
+        <div><input type="range" id="single_thread" name="single_thread" min="0" max="1000" value="{{param.single_thread}}" /></div>
+
+        <p> CPU threads priority: </p>
+        <div><input type="range" id="threads" name="threads" min="0" max="1000" value="{{param.threads}}" /></div>
+
+        <p> Low CPU TDP priority: </p>
+        <div><input type="range" id="low_tdp" name="low_tdp" min="0" max="1000" value="{{param.low_tdp}}" /></div>
+
+        <p> Price: </p>
+        <div>
+            <input type="number" id="min_price" name="min_price" placeholder="from" value="{{param.min_price or ""}}" />
+            <input type="number" id="max_price" name="max_price" placeholder="to" value="{{param.max_price or ""}}" />
+        </div>
+
+        <p> Description contains: </p>
+        <div><input type="text" id="search" name="search" value="{{param.search|e}}" /></div>
+
+        <p> CPU vendors (comma separated: intel, amd, apple): </p>
+        <div>
+            <input type="text" id="cpu_include" name="cpu_include" placeholder="only" value="{{param.cpu_include|e}}" />
+            <input type="text" id="cpu_exclude" name="cpu_exclude" placeholder="except" value="{{param.cpu_exclude|e}}" />
+        </div>
+
+        <p> GPU vendors (comma separated: nvidia, amd, intel, apple): </p>
+        <div>
+            <input type="text" id="gpu_include" name="gpu_include" placeholder="only" value="{{param.gpu_include|e}}" />
+            <input type="text" id="gpu_exclude" name="gpu_exclude" placeholder="except" value="{{param.gpu_exclude|e}}" />
+        </div>
+
+        <p><label><input type="checkbox" id="discrete_gpu" name="discrete_gpu" {% if param.discrete_gpu %}checked{% endif %} /> Discrete GPU only</label></p>
+
+        <p><label><input type="checkbox" id="in_stock" name="in_stock" {% if param.in_stock %}checked{% endif %} /> In stock only</label></p>
+
+        <p><label><input type="checkbox" id="hide_ends_soon" name="hide_ends_soon" {% if param.hide_ends_soon %}checked{% endif %} /> Hide ending soon</label></p>
+
+        <p><label><input type="checkbox" id="shop_seller" name="shop_seller" {% if param.shop_seller %}checked{% endif %} /> Sold by shop only, no marketplace sellers</label></p>
+
+        <p><label><input type="checkbox" id="show_stale" name="show_stale" {% if param.show_stale %}checked{% endif %} /> Show laptops not seen in the latest scrape</label></p>
+
+        <p> Ranking: </p>
+        <select id="strategy" name="strategy">
+            <option value="value_for_money">Value for money</option>
+            <option value="raw_performance">Raw performance</option>
+            <option value="performance_under_budget">Performance under max price</option>
+            <option value="pareto_optimal">Pareto optimal only</option>
+        </select>
+
         <p> Laptop quantity: </p>
         <select id="quantity" name="quantity">
             <option value="5">5</option>
@@ -152,6 +461,7 @@ const PAGE_TEMPLATE: &str = r#"
         <input type="submit">
         <script>
             document.getElementById('quantity').value = '{{param.quantity}}';
+            document.getElementById('strategy').value = '{{param.strategy}}';
         </script>
     </form>
     <p>Laptops:</p>
@@ -161,15 +471,121 @@ const PAGE_TEMPLATE: &str = r#"
             <th>CPU</th>
             <th>GPU</th>
             <th>Price</th>
+            <th>Lowest</th>
+            <th>Trend</th>
+            <th>Shop</th>
+            <th>Availability</th>
             <th>Info</th>
         </tr>
         {% for laptop in laptops %}
-        <tr>
+        <tr{% if laptop.laptop.stale %} style="color: gray" title="Not seen in the latest scrape"{% endif %}>
             <td title="{{laptop.laptop.cpu_score}}cpu * {{param.cpu}}% + {{laptop.laptop.gpu_score}}gpu * {{param.gpu}}%">{{laptop.total_score}}</td>
-            <td title="{{laptop.laptop.cpu_name}}">{{laptop.cpu_percent}}%</td>
-            <td title="{{laptop.laptop.gpu_name}}">{{laptop.gpu_percent}}%</td>
+            <td title="{{laptop.laptop.cpu_name}}, single thread {{laptop.laptop.cpu_single_thread_score}}{% if laptop.laptop.cpu_threads %}, {{laptop.laptop.cpu_cores}} cores / {{laptop.laptop.cpu_threads}} threads{% endif %}{% if laptop.laptop.cpu_tdp %}, {{laptop.laptop.cpu_tdp}}W{% endif %}">{{laptop.cpu_percent}}%{% if laptop.laptop.cpu_match_score is not none and laptop.laptop.cpu_match_score < low_confidence %} <span title="CPU matched with {{laptop.laptop.cpu_match_score}}% confidence">&#9888;</span>{% endif %}</td>
+            <td title="{{laptop.laptop.gpu_name}}">{{laptop.gpu_percent}}%{% if laptop.laptop.gpu_match_score is not none and laptop.laptop.gpu_match_score < low_confidence %} <span title="GPU matched with {{laptop.laptop.gpu_match_score}}% confidence">&#9888;</span>{% endif %}</td>
             <td title="Score per price: {{laptop.total_score / laptop.laptop.price}}">{{laptop.laptop.price}}</td>
+            <td>{% if laptop.min_price %}{{laptop.min_price}}{% endif %}</td>
+            <td><a href="/laptop/{{laptop.laptop.id}}">{{laptop.sparkline|safe}}</a></td>
+            <td>{{laptop.laptop.shop}}{% if laptop.laptop.third_party %} / {{laptop.laptop.seller|e}}{% endif %}{% if laptop.laptop.offer_count > 1 %} (+{{laptop.laptop.offer_count - 1}}){% endif %}</td>
+            <td>{{laptop.laptop.availability|replace("_", " ")}}</td>
+            <td><a href="{{laptop.laptop.url}}">{{laptop.laptop.description}}</a></td>
+        </tr>
+        {% endfor %}
+    </table>
+</body>
+</html>
+"#;
+
+const LAPTOP_TEMPLATE: &str = r#"
+<!doctype html>
+
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+
+  <title>{{laptop.description}}</title>
+  <meta name="description" content="Laptop price history">
+  <meta name="author" content="Vitalii">
+</head>
+
+<body>
+    <p><a href="/laptop_selector">Back to list</a></p>
+    <p><a href="{{laptop.url}}">{{laptop.description}}</a></p>
+    {% if laptop.composition %}<p>{{laptop.composition}}</p>{% endif %}
+    <p>CPU: {{laptop.cpu_name}} ({{laptop.cpu_score}}, single thread {{laptop.cpu_single_thread_score}}{% if laptop.cpu_threads %}, {{laptop.cpu_cores}} cores / {{laptop.cpu_threads}} threads{% endif %}{% if laptop.cpu_tdp %}, {{laptop.cpu_tdp}}W{% endif %}){% if laptop.cpu_match_score is not none %}, matched with {{laptop.cpu_match_score}}% confidence{% endif %}</p>
+    <p>GPU: {{laptop.gpu_name}} ({{laptop.gpu_score}}){% if laptop.gpu_match_score is not none %}, matched with {{laptop.gpu_match_score}}% confidence{% endif %}</p>
+    <p>Current price: {{laptop.price}}{% if min_price %}, lowest price: {{min_price}}{% endif %}</p>
+    {% if laptop.stale %}<p>Not seen in the latest scrape, probably it's not sold anymore</p>{% endif %}
+    <table>
+        <tr>
+            <th>Shop</th>
+            <th>Seller</th>
+            <th>Price</th>
+            <th>Availability</th>
+        </tr>
+        {% for offer in offers %}
+        <tr>
+            <td><a href="{{offer.url}}">{{offer.shop}}</a></td>
+            <td>{% if offer.seller %}{{offer.seller|e}}{% endif %}{% if offer.third_party %} (marketplace){% endif %}</td>
+            <td>{{offer.price}}</td>
+            <td>{{offer.availability|replace("_", " ")}}</td>
+        </tr>
+        {% endfor %}
+    </table>
+    <p>Price history in {{laptop.shop}}:</p>
+    <div>{{chart|safe}}</div>
+    <table>
+        <tr>
+            <th>Observed</th>
+            <th>Price</th>
+        </tr>
+        {% for point in history %}
+        <tr>
+            <td>{{point.observed_at}}</td>
+            <td>{{point.price}}</td>
+        </tr>
+        {% endfor %}
+    </table>
+</body>
+</html>
+"#;
+
+const PARETO_TEMPLATE: &str = r#"
+<!doctype html>
+
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+
+  <title>Laptops Pareto frontier</title>
+  <meta name="description" content="Laptops, which are not beaten by cheaper or faster ones">
+  <meta name="author" content="Vitalii">
+</head>
+
+<body>
+    <p><a href="/laptop_selector">Back to list</a></p>
+    <p>Laptops on frontier: {{frontier}} of {{laptops|length}}</p>
+    <table>
+        <tr>
+            <th>Price</th>
+            <th>CPU</th>
+            <th>GPU</th>
+            <th>Info</th>
+            <th>Dominated by</th>
+        </tr>
+        {% for laptop in laptops %}
+        <tr{% if laptop.laptop.stale %} style="color: gray" title="Not seen in the latest scrape"{% endif %}>
+            <td>{{laptop.laptop.price}}</td>
+            <td title="{{laptop.laptop.cpu_name}}">{{laptop.laptop.cpu_score}}</td>
+            <td title="{{laptop.laptop.gpu_name}}">{{laptop.laptop.gpu_score}}</td>
+            {% if laptop.dominated_by %}
             <td><a href="{{laptop.laptop.url}}">{{laptop.laptop.description}}</a></td>
+            <td><a href="{{laptop.dominated_by.url}}" title="{{laptop.dominated_by.price}}, {{laptop.dominated_by.cpu_score}}cpu, {{laptop.dominated_by.gpu_score}}gpu">{{laptop.dominated_by.description}}</a></td>
+            {% else %}
+            <td><b><a href="{{laptop.laptop.url}}">{{laptop.laptop.description}}</a></b></td>
+            <td></td>
+            {% endif %}
         </tr>
         {% endfor %}
     </table>
@@ -177,63 +593,439 @@ const PAGE_TEMPLATE: &str = r#"
 </html>
 "#;
 
-#[derive(Serialize, Deserialize, Default)]
-struct LaptopPriorities {
-    cpu: i64,
-    gpu: i64,
-    quantity: usize,
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaptopPriorities {
+    pub cpu: i64,
+    pub gpu: i64,
+    pub single_thread: i64,
+    pub threads: i64,
+    /// the lower cpu tdp, the higher score, unknown tdp gives nothing
+    pub low_tdp: i64,
+    pub quantity: usize,
+    pub strategy: Strategy,
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub min_price: Option<i64>,
+    /// also used as budget by [`Strategy::PerformanceUnderBudget`]
+    #[serde(deserialize_with = "deserialize_optional_number")]
+    pub max_price: Option<i64>,
+    /// case insensitive substring of description
+    pub search: String,
+    /// comma separated vendors, see [`cpu_vendor`] and [`gpu_vendor`]
+    pub cpu_include: String,
+    pub cpu_exclude: String,
+    pub gpu_include: String,
+    pub gpu_exclude: String,
+    #[serde(deserialize_with = "deserialize_checkbox")]
+    pub discrete_gpu: bool,
+    /// skip offers, which are out of stock
+    #[serde(deserialize_with = "deserialize_checkbox")]
+    pub in_stock: bool,
+    /// skip offers, which are running out
+    #[serde(deserialize_with = "deserialize_checkbox")]
+    pub hide_ends_soon: bool,
+    /// skip offers, which are sold by marketplace seller
+    #[serde(deserialize_with = "deserialize_checkbox")]
+    pub shop_seller: bool,
+    /// show offers, which were not seen in the latest scrape, see [`LaptopView::stale`]
+    #[serde(deserialize_with = "deserialize_checkbox")]
+    pub show_stale: bool,
+}
+
+impl Default for LaptopPriorities {
+    fn default() -> Self {
+        Self {
+            cpu: 100,
+            gpu: 0,
+            single_thread: 0,
+            threads: 0,
+            low_tdp: 0,
+            quantity: 10,
+            strategy: Strategy::default(),
+            min_price: None,
+            max_price: None,
+            search: String::new(),
+            cpu_include: String::new(),
+            cpu_exclude: String::new(),
+            gpu_include: String::new(),
+            gpu_exclude: String::new(),
+            discrete_gpu: false,
+            in_stock: false,
+            hide_ends_soon: false,
+            shop_seller: false,
+            show_stale: false,
+        }
+    }
+}
+
+/// Checks vendor against comma separated include and exclude lists, empty list means no filter
+fn vendor_allowed(vendor: &str, include: &str, exclude: &str) -> bool {
+    let contains = |list: &str| {
+        list.split(',')
+            .map(str::trim)
+            .any(|item| item.eq_ignore_ascii_case(vendor))
+    };
+    (include.trim().is_empty() || contains(include)) && !contains(exclude)
+}
+
+impl LaptopPriorities {
+    /// Checks both laptop and its offer, so laptop is shown with its best matching offer
+    fn matches(&self, laptop: &LaptopView) -> bool {
+        self.min_price.is_none_or(|min| laptop.price >= min)
+            && self.max_price.is_none_or(|max| laptop.price <= max)
+            && laptop
+                .description
+                .to_lowercase()
+                .contains(&self.search.trim().to_lowercase())
+            && vendor_allowed(
+                cpu_vendor(&laptop.cpu_name),
+                &self.cpu_include,
+                &self.cpu_exclude,
+            )
+            && vendor_allowed(
+                gpu_vendor(&laptop.gpu_name),
+                &self.gpu_include,
+                &self.gpu_exclude,
+            )
+            && !(self.discrete_gpu && is_integrated_gpu(&laptop.gpu_name))
+            && !(self.in_stock && laptop.availability == "out_of_stock")
+            && !(self.hide_ends_soon && laptop.availability == "ends_soon")
+            && !(self.shop_seller && laptop.third_party)
+            && (self.show_stale || !laptop.stale)
+    }
+}
+
+/// Html forms send empty string for empty number inputs, json sends number or null
+fn deserialize_optional_number<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Number(i64),
+        Text(String),
+    }
+    match Option::<Number>::deserialize(deserializer)? {
+        Some(Number::Number(number)) => Ok(Some(number)),
+        Some(Number::Text(text)) if text.trim().is_empty() => Ok(None),
+        Some(Number::Text(text)) => text.trim().parse().map(Some).map_err(de::Error::custom),
+        None => Ok(None),
+    }
+}
+
+/// Html forms send "on" for checked checkbox and nothing otherwise, json sends bool
+fn deserialize_checkbox<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Checkbox {
+        Bool(bool),
+        Text(String),
+    }
+    Ok(match Checkbox::deserialize(deserializer)? {
+        Checkbox::Bool(value) => value,
+        Checkbox::Text(text) => matches!(text.as_str(), "on" | "true" | "1"),
+    })
 }
 
 #[derive(Serialize)]
-struct ScoredLaptop<'a> {
-    laptop: &'a LaptopView,
-    total_score: i64,
-    cpu_percent: i64,
-    gpu_percent: i64,
+pub struct ScoredLaptop<'a> {
+    pub laptop: &'a LaptopView,
+    pub total_score: i64,
+    pub cpu_percent: i64,
+    pub gpu_percent: i64,
+    pub min_price: Option<i64>,
+    pub sparkline: String,
+}
+
+/// Maximal values over all laptops, never zero
+#[derive(Clone, Copy)]
+pub struct Maximums {
+    pub cpu: i64,
+    pub gpu: i64,
+    pub single_thread: i64,
+    pub threads: i64,
+    pub tdp: i64,
+}
+
+impl Maximums {
+    fn of(laptops: &[LaptopView]) -> Self {
+        // avoid division by zero on empty tables
+        let max = |metric: fn(&LaptopView) -> i64| {
+            laptops.iter().map(metric).max().unwrap_or_default().max(1)
+        };
+        Self {
+            cpu: max(|laptop| laptop.cpu_score),
+            gpu: max(|laptop| laptop.gpu_score),
+            single_thread: max(|laptop| laptop.cpu_single_thread_score),
+            threads: max(|laptop| laptop.cpu_threads.unwrap_or_default()),
+            tdp: max(|laptop| laptop.cpu_tdp.unwrap_or_default()),
+        }
+    }
+}
+
+/// Laptops list together with maximal scores, used to compute percents
+pub struct LaptopSnapshot {
+    /// Every laptop with its preferred offer, as returned by [`get_laptops`]
+    pub laptops: Vec<LaptopView>,
+    /// Every laptop with each of its offers, as returned by [`get_laptop_offers`]
+    pub offers: Vec<LaptopView>,
+    pub maximums: Maximums,
+    /// observed prices by offer id, oldest first, see [`LaptopView::offer_id`]
+    pub price_histories: HashMap<i64, Vec<i64>>,
+}
+
+impl LaptopSnapshot {
+    pub async fn load(pool: Arc<SqlitePool>) -> Result<Self, Error> {
+        let offers = get_laptop_offers(pool.clone()).await?;
+        // same as get_laptops, without querying offers again
+        let mut laptops = offers.clone();
+        laptops.dedup_by_key(|laptop| laptop.id);
+        let price_histories = get_price_histories(pool).await?;
+        let maximums = Maximums::of(&laptops);
+        Ok(Self {
+            laptops,
+            offers,
+            maximums,
+            price_histories,
+        })
+    }
+}
+
+/// Swappable handle to the current laptops snapshot, shared between handlers
+#[derive(Clone)]
+pub struct LaptopsHandle {
+    pool: Arc<SqlitePool>,
+    database_path: Option<PathBuf>,
+    snapshot: Arc<RwLock<Arc<LaptopSnapshot>>>,
+}
+
+impl LaptopsHandle {
+    pub async fn load(settings: &DatabaseSettings) -> Result<Self, Error> {
+        let pool = Arc::new(connect(settings).await?);
+        let snapshot = LaptopSnapshot::load(pool.clone()).await?;
+        Ok(Self {
+            pool,
+            database_path: database_path(&settings.url),
+            snapshot: Arc::new(RwLock::new(Arc::new(snapshot))),
+        })
+    }
+
+    pub fn current(&self) -> Arc<LaptopSnapshot> {
+        self.snapshot.read().unwrap().clone()
+    }
+
+    /// Loads new snapshot and swaps it in, returns count of loaded laptops
+    pub async fn reload(&self) -> Result<usize, Error> {
+        let snapshot = LaptopSnapshot::load(self.pool.clone()).await?;
+        let count = snapshot.laptops.len();
+        *self.snapshot.write().unwrap() = Arc::new(snapshot);
+        Ok(count)
+    }
+
+    fn database_modified(&self) -> Option<SystemTime> {
+        let path = self.database_path.as_ref()?;
+        let mut wal_path = path.clone().into_os_string();
+        wal_path.push("-wal");
+        [path.as_os_str(), wal_path.as_os_str()]
+            .into_iter()
+            .filter_map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+            })
+            .max()
+    }
+
+    /// Checks database file every `poll_interval` and reloads snapshot when it was modified,
+    /// or when `reload_interval` has passed since last reload
+    pub fn spawn_reloader(
+        &self,
+        reload_interval: Option<Duration>,
+        poll_interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let handle = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(poll_interval);
+            let mut last_modified = handle.database_modified();
+            let mut last_reload = Instant::now();
+            loop {
+                ticker.tick().await;
+                let modified = handle.database_modified();
+                let expired =
+                    reload_interval.is_some_and(|interval| last_reload.elapsed() >= interval);
+                if modified == last_modified && !expired {
+                    continue;
+                }
+                last_modified = modified;
+                last_reload = Instant::now();
+                match handle.reload().await {
+                    Ok(count) => println!("Reloaded {count} laptops"),
+                    Err(err) => println!("Laptops reload failed: {err}"),
+                }
+            }
+        })
+    }
+}
+
+/// Extracts file path from sqlite url, `None` for in-memory databases
+fn database_path(url: &str) -> Option<PathBuf> {
+    let path = url
+        .strip_prefix("sqlite://")
+        .or_else(|| url.strip_prefix("sqlite:"))?
+        .split('?')
+        .next()?;
+    if path.is_empty() || path == ":memory:" {
+        None
+    } else {
+        Some(PathBuf::from(path))
+    }
+}
+
+/// Scores filtered laptops by priorities, ranks them by chosen strategy and keeps `quantity` best
+pub fn score_laptops<'a>(
+    snapshot: &'a LaptopSnapshot,
+    params: &LaptopPriorities,
+) -> Vec<ScoredLaptop<'a>> {
+    let maximums = snapshot.maximums;
+    let mut shown = HashSet::new();
+    let scored_laptops = snapshot
+        .offers
+        .iter()
+        .filter(|laptop| params.matches(laptop))
+        // preferred offers go first, so laptop takes the first matching one
+        .filter(|laptop| shown.insert(laptop.id))
+        .map(|laptop| {
+            let prices = snapshot
+                .price_histories
+                .get(&laptop.offer_id)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            ScoredLaptop {
+                laptop,
+                total_score: laptop.cpu_score * params.cpu / maximums.cpu
+                    + laptop.gpu_score * params.gpu / maximums.gpu
+                    + laptop.cpu_single_thread_score * params.single_thread
+                        / maximums.single_thread
+                    + laptop.cpu_threads.unwrap_or_default() * params.threads / maximums.threads
+                    + laptop
+                        .cpu_tdp
+                        .map(|tdp| (maximums.tdp - tdp) * params.low_tdp / maximums.tdp)
+                        .unwrap_or_default(),
+                cpu_percent: laptop.cpu_score * 100 / maximums.cpu,
+                gpu_percent: laptop.gpu_score * 100 / maximums.gpu,
+                min_price: prices.iter().min().copied(),
+                sparkline: price_chart(prices, 100, 20),
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut sorted_laptops = params
+        .strategy
+        .scorer(params.max_price)
+        .rank(scored_laptops);
+    sorted_laptops.truncate(params.quantity);
+    sorted_laptops
 }
 
 async fn laptop_request_handler(
-    Extension(laptops): Extension<Arc<Vec<LaptopView>>>,
-    Extension(maximums): Extension<(i64, i64)>,
+    Extension(laptops): Extension<LaptopsHandle>,
     params: String,
 ) -> Html<String> {
     let params: LaptopPriorities = serde_urlencoded::from_str(&params).unwrap_or_default();
-    let mut sorted_laptops = laptops
-        .as_ref()
-        .iter()
-        .map(|laptop| ScoredLaptop {
-            laptop,
-            total_score: laptop.cpu_score * params.cpu / maximums.0
-                + laptop.gpu_score * params.gpu / maximums.1,
-            cpu_percent: laptop.cpu_score * 100 / maximums.0,
-            gpu_percent: laptop.gpu_score * 100 / maximums.1,
-        })
-        .collect::<Vec<_>>();
-    sorted_laptops.sort_by_key(|laptop| laptop.laptop.price * 1000 / (laptop.total_score + 1));
-    let page = render!(PAGE_TEMPLATE,param=>params,laptops=>&sorted_laptops[0..params.quantity]);
+    let snapshot = laptops.current();
+    let sorted_laptops = score_laptops(&snapshot, &params);
+    let page = render!(
+        PAGE_TEMPLATE,
+        param=>params,
+        laptops=>sorted_laptops,
+        low_confidence=>LOW_CONFIDENCE_MATCH
+    );
     Html(page)
 }
 
-async fn default_laptop_request_handler(
-    laptops: Extension<Arc<Vec<LaptopView>>>,
-    maximums: Extension<(i64, i64)>,
-) -> Html<String> {
-    laptop_request_handler(laptops, maximums, String::from("cpu=100&gpu=0&quantity=10")).await
+async fn default_laptop_request_handler(laptops: Extension<LaptopsHandle>) -> Html<String> {
+    laptop_request_handler(laptops, String::from("cpu=100&gpu=0&quantity=10")).await
+}
+
+async fn api_laptops_query_handler(
+    Extension(laptops): Extension<LaptopsHandle>,
+    Query(params): Query<LaptopPriorities>,
+) -> Json<serde_json::Value> {
+    let snapshot = laptops.current();
+    let sorted_laptops = score_laptops(&snapshot, &params);
+    Json(serde_json::json!(sorted_laptops))
+}
+
+async fn api_laptops_json_handler(
+    Extension(laptops): Extension<LaptopsHandle>,
+    Json(params): Json<LaptopPriorities>,
+) -> Json<serde_json::Value> {
+    let snapshot = laptops.current();
+    let sorted_laptops = score_laptops(&snapshot, &params);
+    Json(serde_json::json!(sorted_laptops))
 }
 
-pub async fn prepare_laptop_requests_router() -> Router {
-    let pool = Arc::new(connect().await);
-    let laptops = Arc::new(get_laptops(pool).await.unwrap());
-    let max_scores = (
-        laptops.iter().map(|laptop| laptop.cpu_score).max().unwrap(),
-        laptops.iter().map(|laptop| laptop.gpu_score).max().unwrap(),
+async fn pareto_request_handler(Extension(laptops): Extension<LaptopsHandle>) -> Html<String> {
+    let snapshot = laptops.current();
+    let marked = pareto_frontier(&snapshot.laptops);
+    let frontier = marked
+        .iter()
+        .filter(|laptop| laptop.dominated_by.is_none())
+        .count();
+    let page = render!(PARETO_TEMPLATE,laptops=>marked,frontier=>frontier);
+    Html(page)
+}
+
+async fn laptop_history_handler(
+    Extension(laptops): Extension<LaptopsHandle>,
+    Path(id): Path<i64>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let snapshot = laptops.current();
+    let Some(laptop) = snapshot.laptops.iter().find(|laptop| laptop.id == id) else {
+        return Err((StatusCode::NOT_FOUND, format!("Laptop {id} not found")));
+    };
+    let offers = get_offers(laptops.pool.clone(), id)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    let history = get_price_history(laptops.pool.clone(), laptop.offer_id)
+        .await
+        .map_err(|err| (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?;
+    let prices = history.iter().map(|point| point.price).collect::<Vec<_>>();
+    let page = render!(
+        LAPTOP_TEMPLATE,
+        laptop=>laptop,
+        offers=>offers,
+        history=>history,
+        min_price=>prices.iter().min(),
+        chart=>price_chart(&prices, 600, 150)
     );
+    Ok(Html(page))
+}
+
+async fn reload_request_handler(
+    Extension(laptops): Extension<LaptopsHandle>,
+) -> Result<String, (StatusCode, String)> {
+    match laptops.reload().await {
+        Ok(count) => Ok(format!("Reloaded {count} laptops")),
+        Err(err) => Err((StatusCode::INTERNAL_SERVER_ERROR, err.to_string())),
+    }
+}
 
+pub fn laptop_requests_router(laptops: LaptopsHandle) -> Router {
     Router::new()
         .route(
             "/laptop_selector",
             post(laptop_request_handler).get(default_laptop_request_handler),
         )
+        .route("/laptop_selector/pareto", get(pareto_request_handler))
+        .route(
+            "/api/laptops",
+            get(api_laptops_query_handler).post(api_laptops_json_handler),
+        )
+        .route("/laptop/:id", get(laptop_history_handler))
+        .route("/admin/reload", post(reload_request_handler))
         .layer(Extension(laptops))
-        .layer(Extension(max_scores))
+}
+
+pub async fn prepare_laptop_requests_router(settings: &DatabaseSettings) -> Result<Router, Error> {
+    Ok(laptop_requests_router(LaptopsHandle::load(settings).await?))
 }
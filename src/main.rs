@@ -1,11 +1,79 @@
-use laptop_selector::prepare_laptop_requests_router;
-use std::net::SocketAddr;
+use clap::{Parser, Subcommand};
+use laptop_selector::{
+    connect, ensure_migrated, get_database_configuration, get_migrations_status,
+    laptop_requests_router, open_read_only, Error, LaptopsHandle,
+};
+use std::{net::SocketAddr, time::Duration};
+
+#[derive(Subcommand)]
+enum Command {
+    /// Applies pending migrations and prints status of every migration
+    Migrate {
+        #[clap(long)]
+        /// Only report migrations status, fail if some are pending
+        check: bool,
+    },
+}
+
+#[derive(Parser, Default)]
+#[clap(version, about)]
+/// Serves laptop selector page
+struct Arguments {
+    #[clap(short, long)]
+    /// Database url [default: sqlite://laptops.db]
+    database_url: Option<String>,
+
+    #[clap(long)]
+    /// Refuse to start if database has pending migrations instead of applying them
+    require_migrated: bool,
+
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    /// Reload laptops from database every given number of seconds
+    reload_interval: Option<u64>,
+
+    #[clap(long, default_value_t = 5, value_parser = clap::value_parser!(u64).range(1..))]
+    /// How often (in seconds) to check database file for changes
+    watch_interval: u64,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
 
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<(), Error> {
+    let arguments = Arguments::parse();
+    let database_settings = get_database_configuration(arguments.database_url)?;
+
+    if let Some(Command::Migrate { check }) = arguments.command {
+        let pool = if check {
+            open_read_only(&database_settings).await?
+        } else {
+            Some(connect(&database_settings).await?)
+        };
+        for migration in get_migrations_status(pool.as_ref()).await? {
+            let status = if migration.applied {
+                "applied"
+            } else {
+                "pending"
+            };
+            println!("{} {} {status}", migration.version, migration.description);
+        }
+        return ensure_migrated(pool.as_ref()).await;
+    }
+
+    if arguments.require_migrated {
+        ensure_migrated(open_read_only(&database_settings).await?.as_ref()).await?;
+    }
+
+    let laptops = LaptopsHandle::load(&database_settings).await?;
+    laptops.spawn_reloader(
+        arguments.reload_interval.map(Duration::from_secs),
+        Duration::from_secs(arguments.watch_interval),
+    );
+
     let addr = SocketAddr::from(([127, 0, 0, 1], 80));
     axum::Server::bind(&addr)
-        .serve(prepare_laptop_requests_router().await.into_make_service())
-        .await
-        .unwrap();
+        .serve(laptop_requests_router(laptops).into_make_service())
+        .await?;
+    Ok(())
 }
@@ -0,0 +1,344 @@
+use crate::Cpu;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::{Captures, Regex};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Which devices are looked for in laptop composition
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceKind {
+    Cpu,
+    Gpu,
+}
+
+/// Recognizes device in composition and builds its name the way benchmark sites write it
+struct Rule {
+    pattern: Regex,
+    name: fn(&Captures) -> String,
+}
+
+impl Rule {
+    fn new(pattern: &str, name: fn(&Captures) -> String) -> Self {
+        Self {
+            pattern: Regex::new(pattern).unwrap(),
+            name,
+        }
+    }
+}
+
+/// Optional capture group, empty if it didn't participate in match
+fn group<'a>(captures: &'a Captures, index: usize) -> &'a str {
+    captures.get(index).map_or("", |group| group.as_str())
+}
+
+fn apple_rule() -> Rule {
+    Rule::new(r"(?i)\bapple\s+(m[1-4])(?:\s+(pro|max|ultra))?\b", |c| {
+        let mut name = format!("Apple {}", group(c, 1).to_uppercase());
+        match group(c, 2).to_lowercase().as_str() {
+            "pro" => name.push_str(" Pro"),
+            "max" => name.push_str(" Max"),
+            "ultra" => name.push_str(" Ultra"),
+            _ => {}
+        }
+        name
+    })
+}
+
+/// More specific rules go first: "Core Ultra" before "Core", "Ryzen AI" before "Ryzen"
+fn rules(kind: DeviceKind) -> &'static [Rule] {
+    static CPU_RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    static GPU_RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    match kind {
+        DeviceKind::Cpu => CPU_RULES.get_or_init(|| {
+            vec![
+                Rule::new(
+                    r"(?i)\b(?:intel\s+)?(?:core\s+)?ultra\s+([3579])\s+(\d{3}[a-z]{0,2})\b",
+                    |c| {
+                        format!(
+                            "Intel Core Ultra {} {}",
+                            group(c, 1),
+                            group(c, 2).to_uppercase()
+                        )
+                    },
+                ),
+                Rule::new(
+                    r"(?i)\b(?:intel\s+)?(?:core\s+)?(i[3579])[\s-]?(\d{4,5}[a-z]{0,2})\b",
+                    |c| {
+                        format!(
+                            "Intel Core {}-{}",
+                            group(c, 1).to_lowercase(),
+                            group(c, 2).to_uppercase()
+                        )
+                    },
+                ),
+                Rule::new(r"(?i)\bintel\s+core\s+([3579])\s+(\d{3}[a-z]{0,2})\b", |c| {
+                    format!("Intel Core {} {}", group(c, 1), group(c, 2).to_uppercase())
+                }),
+                Rule::new(
+                    r"(?i)\b(?:amd\s+)?ryzen\s+ai\s+([3579])\s+(?:(hx)\s+)?(\d{3})\b",
+                    |c| {
+                        let hx = if group(c, 2).is_empty() { "" } else { "HX " };
+                        format!("AMD Ryzen AI {} {hx}{}", group(c, 1), group(c, 3))
+                    },
+                ),
+                Rule::new(
+                    r"(?i)\b(?:amd\s+)?ryzen\s+([3579])\s+(?:(pro)\s+)?(\d{4}[a-z]{0,3})\b",
+                    |c| {
+                        let pro = if group(c, 2).is_empty() { "" } else { "PRO " };
+                        format!(
+                            "AMD Ryzen {} {pro}{}",
+                            group(c, 1),
+                            group(c, 3).to_uppercase()
+                        )
+                    },
+                ),
+                apple_rule(),
+            ]
+        }),
+        DeviceKind::Gpu => GPU_RULES.get_or_init(|| {
+            vec![
+                Rule::new(
+                    r"(?i)\b(?:nvidia\s+)?(?:geforce\s+)?(rtx|gtx|mx)\s*(\d{3,4})(?:\s*(ti|super)\b)?\b",
+                    |c| {
+                        let suffix = match group(c, 3).to_lowercase().as_str() {
+                            "ti" => " Ti",
+                            "super" => " SUPER",
+                            _ => "",
+                        };
+                        let series = group(c, 1).to_uppercase();
+                        // "GeForce MX550", but "GeForce RTX 3050"
+                        let separator = if series == "MX" { "" } else { " " };
+                        format!("GeForce {series}{separator}{}{suffix}", group(c, 2))
+                    },
+                ),
+                Rule::new(
+                    r"(?i)\b(?:amd\s+)?radeon\s+(?:(rx)\s+)?(\d{3,4}[a-z]{0,2})\b",
+                    |c| {
+                        let rx = if group(c, 1).is_empty() { "" } else { "RX " };
+                        format!("Radeon {rx}{}", group(c, 2).to_uppercase())
+                    },
+                ),
+                apple_rule(),
+            ]
+        }),
+    }
+}
+
+/// Normalized device name from first rule, which fires on composition
+pub fn extract_device(composition: &str, kind: DeviceKind) -> Option<String> {
+    rules(kind).iter().find_map(|rule| {
+        rule.pattern
+            .captures(composition)
+            .map(|captures| (rule.name)(&captures))
+    })
+}
+
+/// Splits composition like "Intel Core i5-1235U (1.3 - 4.4 ГГц) / RAM 8 ГБ / ..." into devices
+pub fn composition_devices(composition: &str) -> Vec<&str> {
+    composition
+        .split('/')
+        .map(|device| device.split('(').next().unwrap())
+        .map(str::trim)
+        .collect()
+}
+
+/// Lowercase words of name without vendor, "Intel Core i5-12450H" -> ["core", "i5", "12450h"]
+fn name_tokens(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .replace(['-', ','], " ")
+        .split_whitespace()
+        .filter(|token| !matches!(*token, "intel" | "amd" | "nvidia" | "apple"))
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Finds device with normalized name. Laptop variant of gpu is preferred over desktop one,
+/// then exact name, then the shortest name starting with it (e.g. "Apple M2 8 Core 3500 MHz")
+fn find_normalized(name: &str, devices: &[Cpu]) -> Option<(usize, i64)> {
+    let wanted = name_tokens(name);
+    let mut laptop_variant = wanted.clone();
+    laptop_variant.extend([String::from("laptop"), String::from("gpu")]);
+
+    let mut exact = None;
+    let mut shortest: Option<(usize, usize)> = None;
+    for (index, device) in devices.iter().enumerate() {
+        let tokens = name_tokens(&device.name);
+        if tokens == laptop_variant {
+            return Some((index, 100));
+        }
+        if tokens == wanted {
+            exact = exact.or(Some(index));
+        } else if tokens.starts_with(&wanted)
+            && shortest.is_none_or(|(_, length)| tokens.len() < length)
+        {
+            shortest = Some((index, tokens.len()));
+        }
+    }
+    exact
+        .map(|index| (index, 100))
+        .or(shortest.map(|(index, _)| (index, 80)))
+}
+
+/// Index of best fuzzy matching device and confidence in percent of ideal match,
+/// index 0 (unknown device) with zero confidence if nothing matches
+pub fn fuzzy_best_match(fragments: &[&str], devices: &[Cpu]) -> (usize, i64) {
+    let matcher = SkimMatcherV2::default();
+    let mut device_index = 0;
+    let mut best_score = 0;
+    for (index, device) in devices.iter().enumerate() {
+        for fragment in fragments {
+            if let Some(score) = matcher.fuzzy_match(fragment, &device.name) {
+                if score > best_score {
+                    best_score = score;
+                    device_index = index;
+                }
+            }
+        }
+    }
+    if best_score == 0 {
+        return (device_index, 0);
+    }
+    let name = &devices[device_index].name;
+    let ideal = matcher.fuzzy_match(name, name).unwrap_or(best_score).max(1);
+    (device_index, (best_score * 100 / ideal).min(100))
+}
+
+/// Index of device from composition and match confidence in percent.
+/// Recognized by rules device is looked up by its normalized name, fuzzy matching
+/// of composition fragments is used only if no rule fires
+pub fn get_best_match(composition: &str, kind: DeviceKind, devices: &[Cpu]) -> (usize, i64) {
+    match extract_device(composition, kind) {
+        Some(name) => {
+            find_normalized(&name, devices).unwrap_or_else(|| fuzzy_best_match(&[&name], devices))
+        }
+        None => fuzzy_best_match(&composition_devices(composition), devices),
+    }
+}
+
+/// Lowercase characters of text, device name can be fuzzy matched in fragment only if
+/// all its characters are in the fragment
+fn char_set(text: &str) -> HashSet<char> {
+    text.chars().flat_map(char::to_lowercase).collect()
+}
+
+/// Devices prepared for matching, built once per run and shared between tasks.
+/// Gives the same result as [`get_best_match`], but doesn't walk through all devices
+pub struct DeviceIndex {
+    devices: Vec<Cpu>,
+    /// name tokens -> indices of devices, ordered for prefix search
+    names: BTreeMap<Vec<String>, Vec<usize>>,
+    /// character -> indices of devices, which have it as the rarest one in name
+    rarest_chars: HashMap<char, Vec<usize>>,
+    char_sets: Vec<HashSet<char>>,
+}
+
+impl DeviceIndex {
+    pub fn new(devices: Vec<Cpu>) -> Self {
+        let mut names: BTreeMap<Vec<String>, Vec<usize>> = BTreeMap::new();
+        let char_sets = devices
+            .iter()
+            .map(|device| char_set(&device.name))
+            .collect::<Vec<_>>();
+        let mut frequencies: HashMap<char, usize> = HashMap::new();
+        for chars in &char_sets {
+            for &c in chars {
+                *frequencies.entry(c).or_default() += 1;
+            }
+        }
+        let mut rarest_chars: HashMap<char, Vec<usize>> = HashMap::new();
+        for (index, (device, chars)) in devices.iter().zip(&char_sets).enumerate() {
+            names
+                .entry(name_tokens(&device.name))
+                .or_default()
+                .push(index);
+            if let Some(&rarest) = chars.iter().min_by_key(|&c| (frequencies[c], *c)) {
+                rarest_chars.entry(rarest).or_default().push(index);
+            }
+        }
+        Self {
+            devices,
+            names,
+            rarest_chars,
+            char_sets,
+        }
+    }
+
+    pub fn devices(&self) -> &[Cpu] {
+        &self.devices
+    }
+
+    /// Same as [`find_normalized`], indices in lists are ascending, so first is the one
+    /// linear search would find
+    fn find_normalized(&self, name: &str) -> Option<(usize, i64)> {
+        let wanted = name_tokens(name);
+        let mut laptop_variant = wanted.clone();
+        laptop_variant.extend([String::from("laptop"), String::from("gpu")]);
+        if let Some(indices) = self.names.get(&laptop_variant) {
+            return Some((indices[0], 100));
+        }
+        if let Some(indices) = self.names.get(&wanted) {
+            return Some((indices[0], 100));
+        }
+        self.names
+            .range(wanted.clone()..)
+            .take_while(|(tokens, _)| tokens.starts_with(&wanted))
+            .map(|(tokens, indices)| (tokens.len(), indices[0]))
+            .min()
+            .map(|(_, index)| (index, 80))
+    }
+
+    /// Same as [`fuzzy_best_match`], but only devices, which characters are all in some
+    /// fragment, are compared
+    fn fuzzy_best_match(&self, fragments: &[&str]) -> (usize, i64) {
+        let fragment_chars = fragments
+            .iter()
+            .map(|fragment| char_set(fragment))
+            .collect::<Vec<_>>();
+        let mut candidates = fragment_chars
+            .iter()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|c| self.rarest_chars.get(c))
+            .flatten()
+            .copied()
+            .filter(|&index| {
+                fragment_chars
+                    .iter()
+                    .any(|chars| self.char_sets[index].is_subset(chars))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+
+        let matcher = SkimMatcherV2::default();
+        let mut device_index = 0;
+        let mut best_score = 0;
+        for index in candidates {
+            for fragment in fragments {
+                if let Some(score) = matcher.fuzzy_match(fragment, &self.devices[index].name) {
+                    if score > best_score {
+                        best_score = score;
+                        device_index = index;
+                    }
+                }
+            }
+        }
+        if best_score == 0 {
+            return (device_index, 0);
+        }
+        let name = &self.devices[device_index].name;
+        let ideal = matcher.fuzzy_match(name, name).unwrap_or(best_score).max(1);
+        (device_index, (best_score * 100 / ideal).min(100))
+    }
+
+    /// Index of device from composition and match confidence in percent, see [`get_best_match`]
+    pub fn best_match(&self, composition: &str, kind: DeviceKind) -> (usize, i64) {
+        match extract_device(composition, kind) {
+            Some(name) => self
+                .find_normalized(&name)
+                .unwrap_or_else(|| self.fuzzy_best_match(&[&name])),
+            None => self.fuzzy_best_match(&composition_devices(composition)),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use crate::LaptopView;
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct ParetoLaptop<'a> {
+    pub laptop: &'a LaptopView,
+    /// `None` for laptops on the frontier
+    pub dominated_by: Option<&'a LaptopView>,
+}
+
+/// `first` is not more expensive, not slower in any benchmark and better in at least one aspect
+pub fn dominates(first: &LaptopView, second: &LaptopView) -> bool {
+    first.price <= second.price
+        && first.cpu_score >= second.cpu_score
+        && first.gpu_score >= second.gpu_score
+        && (first.price < second.price
+            || first.cpu_score > second.cpu_score
+            || first.gpu_score > second.gpu_score)
+}
+
+/// Marks every laptop as dominated or not over (price, cpu_score, gpu_score),
+/// for dominated ones the cheapest dominating laptop is chosen, stale laptops don't dominate.
+/// Frontier goes first, both parts ordered by price
+pub fn pareto_frontier(laptops: &[LaptopView]) -> Vec<ParetoLaptop<'_>> {
+    let mut marked = laptops
+        .iter()
+        .map(|laptop| ParetoLaptop {
+            laptop,
+            dominated_by: laptops
+                .iter()
+                .filter(|other| !other.stale && dominates(other, laptop))
+                .min_by_key(|other| (other.price, -(other.cpu_score + other.gpu_score))),
+        })
+        .collect::<Vec<_>>();
+    marked.sort_by_key(|marked| (marked.dominated_by.is_some(), marked.laptop.price));
+    marked
+}
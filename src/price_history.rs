@@ -0,0 +1,135 @@
+use crate::Error;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(Debug, Serialize)]
+pub struct PricePoint {
+    pub price: i64,
+    pub observed_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PriceDrop {
+    pub laptop_id: i64,
+    pub shop: String,
+    pub description: String,
+    pub url: String,
+    pub previous_price: i64,
+    pub price: i64,
+}
+
+/// Saves observed price of shop offer, should be called on every scrape
+pub async fn record_price(pool: &SqlitePool, offer_id: i64, price: i64) -> Result<(), Error> {
+    sqlx::query!(
+        "INSERT INTO laptop_price_history(offer_id, price) VALUES ($1, $2)",
+        offer_id,
+        price
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// All observed prices of shop offer, oldest first
+pub async fn get_price_history(
+    pool: Arc<SqlitePool>,
+    offer_id: i64,
+) -> Result<Vec<PricePoint>, Error> {
+    Ok(sqlx::query_as!(
+        PricePoint,
+        "
+            SELECT price, observed_at FROM laptop_price_history
+            WHERE offer_id = $1
+            ORDER BY observed_at ASC, id ASC;
+        ",
+        offer_id
+    )
+    .fetch_all(pool.as_ref())
+    .await?)
+}
+
+/// All-time minimal price of shop offer, `None` if price was never recorded
+pub async fn get_min_price(pool: Arc<SqlitePool>, offer_id: i64) -> Result<Option<i64>, Error> {
+    Ok(sqlx::query_scalar!(
+        "SELECT MIN(price) FROM laptop_price_history WHERE offer_id = $1;",
+        offer_id
+    )
+    .fetch_one(pool.as_ref())
+    .await?)
+}
+
+/// Offers, which last observed price is lower than previous one by more than `percent`
+pub async fn get_price_drops(pool: Arc<SqlitePool>, percent: i64) -> Result<Vec<PriceDrop>, Error> {
+    Ok(sqlx::query_as!(
+        PriceDrop,
+        r#"
+            WITH ranked AS (
+                SELECT offer_id, price, ROW_NUMBER() OVER (
+                    PARTITION BY offer_id ORDER BY observed_at DESC, id DESC
+                ) AS position
+                FROM laptop_price_history
+            )
+            SELECT laptop_model.id as "laptop_id!", offer.shop as "shop!",
+                laptop_model.description as "description!",
+                offer.url as "url!", previous.price as "previous_price!",
+                current.price as "price!"
+            FROM ranked current
+                JOIN ranked previous ON previous.offer_id = current.offer_id
+                    AND previous.position = 2
+                JOIN offer ON offer.id = current.offer_id
+                JOIN laptop_model ON laptop_model.id = offer.model_id
+            WHERE current.position = 1
+                AND current.price * 100 < previous.price * (100 - $1)
+            ORDER BY (previous.price - current.price) * 100 / previous.price DESC;
+        "#,
+        percent
+    )
+    .fetch_all(pool.as_ref())
+    .await?)
+}
+
+/// Observed prices of every shop offer by offer id, oldest first
+pub async fn get_price_histories(pool: Arc<SqlitePool>) -> Result<HashMap<i64, Vec<i64>>, Error> {
+    let rows = sqlx::query!(
+        "
+            SELECT offer_id, price FROM laptop_price_history
+            ORDER BY offer_id ASC, observed_at ASC, id ASC;
+        "
+    )
+    .fetch_all(pool.as_ref())
+    .await?;
+    let mut histories: HashMap<i64, Vec<i64>> = HashMap::new();
+    for row in rows {
+        histories.entry(row.offer_id).or_default().push(row.price);
+    }
+    Ok(histories)
+}
+
+/// Renders prices as inline svg polyline, empty string if there is no trend to show
+pub fn price_chart(prices: &[i64], width: u32, height: u32) -> String {
+    if prices.len() < 2 {
+        return String::new();
+    }
+    let min = *prices.iter().min().unwrap();
+    let max = *prices.iter().max().unwrap();
+    let step = f64::from(width) / (prices.len() - 1) as f64;
+    let points = prices
+        .iter()
+        .enumerate()
+        .map(|(index, &price)| {
+            let y = if max == min {
+                f64::from(height) / 2.0
+            } else {
+                f64::from(height) * (max - price) as f64 / (max - min) as f64
+            };
+            format!("{:.1},{:.1}", index as f64 * step, y)
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        r#"<svg width="{width}" height="{height}" viewBox="-1 -1 {} {}"><polyline fill="none" stroke="currentColor" points="{points}"/></svg>"#,
+        width + 2,
+        height + 2
+    )
+}
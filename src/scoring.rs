@@ -0,0 +1,84 @@
+use crate::{dominates, ScoredLaptop};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Orders scored laptops, best first
+pub trait Scorer {
+    /// May drop laptops, which shouldn't be offered at all by this strategy
+    fn rank<'a>(&self, laptops: Vec<ScoredLaptop<'a>>) -> Vec<ScoredLaptop<'a>>;
+}
+
+/// Cheapest price per weighted score first
+pub struct ValueForMoney;
+
+impl Scorer for ValueForMoney {
+    fn rank<'a>(&self, mut laptops: Vec<ScoredLaptop<'a>>) -> Vec<ScoredLaptop<'a>> {
+        laptops.sort_by_key(|laptop| laptop.laptop.price * 1000 / (laptop.total_score + 1));
+        laptops
+    }
+}
+
+/// Highest weighted score first, cheaper first among equal
+pub struct RawPerformance;
+
+impl Scorer for RawPerformance {
+    fn rank<'a>(&self, mut laptops: Vec<ScoredLaptop<'a>>) -> Vec<ScoredLaptop<'a>> {
+        laptops.sort_by_key(|laptop| (-laptop.total_score, laptop.laptop.price));
+        laptops
+    }
+}
+
+/// Highest weighted score first among laptops not exceeding budget
+pub struct PerformanceUnderBudget {
+    pub budget: Option<i64>,
+}
+
+impl Scorer for PerformanceUnderBudget {
+    fn rank<'a>(&self, mut laptops: Vec<ScoredLaptop<'a>>) -> Vec<ScoredLaptop<'a>> {
+        if let Some(budget) = self.budget {
+            laptops.retain(|laptop| laptop.laptop.price <= budget);
+        }
+        RawPerformance.rank(laptops)
+    }
+}
+
+/// Only laptops on Pareto frontier over (price, cpu_score, gpu_score) like on the Pareto page,
+/// see [`dominates`], ordered by value for money
+pub struct ParetoOptimal;
+
+impl Scorer for ParetoOptimal {
+    fn rank<'a>(&self, mut laptops: Vec<ScoredLaptop<'a>>) -> Vec<ScoredLaptop<'a>> {
+        let optimal = laptops
+            .iter()
+            .filter(|laptop| {
+                !laptops
+                    .iter()
+                    .any(|other| !other.laptop.stale && dominates(other.laptop, laptop.laptop))
+            })
+            .map(|laptop| laptop.laptop.id)
+            .collect::<HashSet<_>>();
+        laptops.retain(|laptop| optimal.contains(&laptop.laptop.id));
+        ValueForMoney.rank(laptops)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    #[default]
+    ValueForMoney,
+    RawPerformance,
+    PerformanceUnderBudget,
+    ParetoOptimal,
+}
+
+impl Strategy {
+    pub fn scorer(self, budget: Option<i64>) -> Box<dyn Scorer> {
+        match self {
+            Strategy::ValueForMoney => Box::new(ValueForMoney),
+            Strategy::RawPerformance => Box::new(RawPerformance),
+            Strategy::PerformanceUnderBudget => Box::new(PerformanceUnderBudget { budget }),
+            Strategy::ParetoOptimal => Box::new(ParetoOptimal),
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/// Guesses vendor of cpu by its benchmark name, lowercase
+pub fn cpu_vendor(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    if name.contains("intel") {
+        "intel"
+    } else if name.contains("amd") || name.contains("ryzen") || name.contains("athlon") {
+        "amd"
+    } else if name.contains("apple") {
+        "apple"
+    } else if name.contains("snapdragon") || name.contains("qualcomm") {
+        "qualcomm"
+    } else if name.contains("mediatek") {
+        "mediatek"
+    } else {
+        "unknown"
+    }
+}
+
+/// Guesses vendor of gpu by its benchmark name, lowercase
+pub fn gpu_vendor(name: &str) -> &'static str {
+    let name = name.to_lowercase();
+    if name.contains("nvidia")
+        || name.contains("geforce")
+        || name.contains("quadro")
+        || name.contains("rtx")
+    {
+        "nvidia"
+    } else if name.contains("amd") || name.contains("radeon") {
+        "amd"
+    } else if name.contains("intel") || name.contains("iris") || name.contains("uhd") {
+        "intel"
+    } else if name.contains("apple") {
+        "apple"
+    } else if name.contains("adreno") {
+        "qualcomm"
+    } else {
+        "unknown"
+    }
+}
+
+/// Integrated graphics are built into cpu: intel except Arc, radeon graphics/vega, apple
+pub fn is_integrated_gpu(name: &str) -> bool {
+    let lowercase = name.to_lowercase();
+    match gpu_vendor(name) {
+        "intel" => !lowercase.contains("arc a"),
+        "amd" => {
+            lowercase.contains("radeon graphics")
+                || lowercase.contains("vega")
+                || [
+                    "610m", "660m", "680m", "740m", "760m", "780m", "880m", "890m",
+                ]
+                .iter()
+                .any(|model| lowercase.contains(model))
+        }
+        "nvidia" => false,
+        _ => true,
+    }
+}
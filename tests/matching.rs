@@ -0,0 +1,68 @@
+use laptop_selector::{
+    evaluate_matching, get_best_match, load_corpus, load_devices, DeviceIndex, DeviceKind,
+    LabelledComposition, MatchingReport,
+};
+use std::path::PathBuf;
+
+fn fixtures() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("fixtures/matching")
+}
+
+fn corpus() -> Vec<LabelledComposition> {
+    load_corpus(&fixtures().join("corpus.json")).unwrap()
+}
+
+fn index(kind: DeviceKind) -> DeviceIndex {
+    let file = match kind {
+        DeviceKind::Cpu => "cpu.json",
+        DeviceKind::Gpu => "gpu.json",
+    };
+    DeviceIndex::new(load_devices(&fixtures().join(file)).unwrap())
+}
+
+/// Prints report, so `cargo test -- --nocapture` shows what went wrong
+fn print_report(label: &str, report: &MatchingReport) {
+    println!(
+        "{label}: precision {:.3}, recall {:.3} on {} compositions",
+        report.precision(),
+        report.recall(),
+        report.samples
+    );
+    for mismatch in &report.mismatches {
+        println!(
+            "  expected {:?}, matched {:?} ({}%): {}",
+            mismatch.expected, mismatch.matched, mismatch.confidence, mismatch.composition
+        );
+    }
+}
+
+#[test]
+fn cpu_matching_precision_and_recall() {
+    let report = evaluate_matching(&corpus(), DeviceKind::Cpu, &index(DeviceKind::Cpu));
+    print_report("cpu", &report);
+    assert!(report.precision() >= 0.95, "cpu precision dropped");
+    assert!(report.recall() >= 0.95, "cpu recall dropped");
+}
+
+#[test]
+fn gpu_matching_precision_and_recall() {
+    let report = evaluate_matching(&corpus(), DeviceKind::Gpu, &index(DeviceKind::Gpu));
+    print_report("gpu", &report);
+    assert!(report.precision() >= 0.95, "gpu precision dropped");
+    assert!(report.recall() >= 0.95, "gpu recall dropped");
+}
+
+#[test]
+fn device_index_agrees_with_linear_matching() {
+    for kind in [DeviceKind::Cpu, DeviceKind::Gpu] {
+        let index = index(kind);
+        for sample in corpus() {
+            assert_eq!(
+                index.best_match(&sample.composition, kind),
+                get_best_match(&sample.composition, kind, index.devices()),
+                "{}",
+                sample.composition
+            );
+        }
+    }
+}